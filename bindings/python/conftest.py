@@ -1,6 +0,0 @@
-import sys
-import os
-
-ROOT = os.path.dirname(__file__)
-if ROOT not in sys.path:
-    sys.path.insert(0, ROOT)
@@ -0,0 +1,174 @@
+import ast
+import inspect
+import json
+import os
+
+import pytest
+
+# CI sets TRANSPORT_CORE_REQUIRE_NATIVE so that a broken build fails the
+# stub check instead of skipping it.
+if os.environ.get("TRANSPORT_CORE_REQUIRE_NATIVE"):
+    import transport_core._native as native
+else:
+    native = pytest.importorskip("transport_core._native")
+
+from transport_core import Client, Outcome, RequestContext
+
+
+def test_rate_limited_keeps_hint():
+    client = Client()
+    decision = client.decide(
+        RequestContext("GET", 1, 3),
+        Outcome.rate_limited(3000),
+    )
+
+    assert decision.action == "RETRY"
+    assert decision.after_ms == 3000
+    assert decision.retry_reason == "RateLimited"
+
+
+def test_second_401_fails_after_refresh():
+    client = Client()
+    ctx = RequestContext("GET", 1, 3)
+
+    first = client.decide(ctx, Outcome.http_status(401), "RefreshAndRetry")
+    second = client.decide(ctx, Outcome.http_status(401), "RefreshAndRetry")
+
+    assert first.action == "REFRESH_AND_RETRY"
+    assert second.action == "FAIL"
+    assert second.fail_reason == "AuthFailed"
+
+
+def test_policy_json_configures_client():
+    client = Client('{"retry": {"hint_mode": "MaxOfHintAndBackoff"}}')
+    decision = client.decide(
+        RequestContext("GET", 1, 3),
+        Outcome.rate_limited(10),
+    )
+
+    assert decision.after_ms == 1500
+
+
+def test_invalid_input_raises():
+    with pytest.raises(ValueError):
+        RequestContext("FETCH", 1, 3)
+
+
+def test_concurrency_slots():
+    client = Client('{"concurrency": {"initial_limit": 1, "max_queue": 1}}')
+
+    assert client.acquire() == "Acquired"
+    assert client.acquire() == "Queued"
+    assert client.acquire() == "Rejected"
+    client.cancel_queued()
+
+    client.release(10, Outcome.timeout())
+    assert client.concurrency_limit() == 1
+    assert client.state()["in_flight"] == 0
+
+    with pytest.raises(ValueError):
+        client.acquire(priority="Urgent")
+
+
+def test_hedging_follows_policy():
+    client = Client()
+    client.set_policy('{"hedge": {"max_hedges": 1, "delay_ms": 50}}')
+    ctx = RequestContext("GET", 1, 3)
+
+    assert client.plan_hedge(ctx) == 50
+    assert client.state()["hedge_stats"]["hedges_pending"] == 1
+    assert client.plan_hedge(ctx, hedges_sent=1) is None
+    client.report_hedge_result(1)
+    assert client.state()["hedge_stats"]["hedges_sent"] == 1
+    assert client.state()["hedge_stats"]["hedges_pending"] == 0
+    assert json.loads(client.policy_json())["hedge"]["max_hedges"] == 1
+
+
+def test_state_round_trip_and_reset():
+    client = Client()
+    ctx = RequestContext("GET", 1, 3)
+    client.decide(ctx, Outcome.http_status(401), "RefreshAndRetry")
+    exported = client.export_state(1000)
+
+    client.reset_auth()
+    assert client.state()["auth"]["refresh_attempted"] is False
+
+    client.import_state(exported)
+    assert client.state()["auth"]["refresh_attempted"] is True
+    client.reset()
+    assert client.state()["auth"]["refresh_attempted"] is False
+
+    with pytest.raises(ValueError):
+        client.import_state('{"version": 99}')
+
+
+def test_explanation_observer_metrics_and_recording():
+    client = Client()
+    ctx = RequestContext("GET", 1, 3)
+    events = []
+    client.set_observer(events.append)
+    client.set_explain(True)
+    client.set_recording(True)
+
+    client.decide(ctx, Outcome.network_error())
+
+    assert [e.decision.action for e in events] == ["RETRY"]
+    assert events[0].ctx.attempt == 1
+    assert client.last_explanation() is not None
+    assert "tc_decisions_total" in client.metrics_prometheus()
+    assert "tc_decisions_total" in [c["name"] for c in client.metrics()["counters"]]
+    lines = [json.loads(line) for line in client.take_recording().splitlines()]
+    assert [line["event"] for line in lines] == ["start", "decide"]
+
+    client.set_observer(None)
+    client.decide(ctx, Outcome.network_error())
+    assert len(events) == 1
+
+    # Observer errors are reported as unraisable, not raised from decide.
+    client.set_observer(lambda event: 1 / 0)
+    assert client.decide(ctx, Outcome.network_error()).action == "RETRY"
+
+
+def _stub_parameters(function, bound):
+    args = function.args.args[1:] if bound else function.args.args
+    defaults = [ast.literal_eval(d) for d in function.args.defaults]
+    defaults = [inspect.Parameter.empty] * (len(args) - len(defaults)) + defaults
+    return [(arg.arg, default) for arg, default in zip(args, defaults)]
+
+
+def _native_parameters(obj):
+    parameters = inspect.signature(obj).parameters.values()
+    return [(p.name, p.default) for p in parameters if p.name != "self"]
+
+
+def test_stubs_match_module():
+    path = os.path.join(os.path.dirname(native.__file__), "_native.pyi")
+    with open(path) as f:
+        tree = ast.parse(f.read())
+
+    stubs = {node.name: node for node in tree.body if isinstance(node, ast.ClassDef)}
+    classes = {
+        name
+        for name, value in vars(native).items()
+        if isinstance(value, type) and not name.startswith("_")
+    }
+    assert set(stubs) == classes
+
+    for name, node in stubs.items():
+        cls = getattr(native, name)
+        functions = {
+            item.name: item for item in node.body if isinstance(item, ast.FunctionDef)
+        }
+        public = {attr for attr in vars(cls) if not attr.startswith("_")}
+        assert set(functions) - {"__init__"} == public, name
+        # Only classes with a constructor carry a signature.
+        assert ("__init__" in functions) == (cls.__text_signature__ is not None), name
+
+        for method, function in functions.items():
+            decorators = {ast.unparse(d) for d in function.decorator_list}
+            if "property" in decorators:
+                continue
+
+            target = cls if method == "__init__" else getattr(cls, method)
+            expected = _stub_parameters(function, "staticmethod" not in decorators)
+            assert _native_parameters(target) == expected, f"{name}.{method}"
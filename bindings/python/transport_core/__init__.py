@@ -0,0 +1,10 @@
+"""
+Python bindings for transport-core.
+
+`transport_core._native` is the extension module built from the Rust
+crate with maturin; its classes are re-exported here.
+"""
+
+from ._native import Client, Decision, DecisionEvent, Outcome, RequestContext
+
+__all__ = ["Client", "Decision", "DecisionEvent", "Outcome", "RequestContext"]
@@ -0,0 +1,107 @@
+# Type stubs for the PyO3 extension module built from core/src/python.rs.
+# tests/test_native.py checks every name and signature against the built
+# module, both ways.
+
+from typing import Any, Callable, Literal, Optional
+
+Method = Literal["GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS"]
+Priority = Literal["Background", "Normal", "Critical"]
+Action = Literal["PROCEED", "RETRY", "REFRESH_AND_RETRY", "FAIL"]
+AuthDecision = Literal["RefreshAndRetry", "Fail"]
+Admission = Literal["Acquired", "Queued", "Rejected"]
+
+class RequestContext:
+    def __init__(
+        self,
+        method: Method,
+        attempt: int,
+        max_attempts: int,
+        idempotency_key: Optional[str] = None,
+        allow_non_idempotent_retry: bool = False,
+        partition: Optional[str] = None,
+        priority: Priority = "Normal",
+    ) -> None: ...
+    @property
+    def attempt(self) -> int: ...
+    @property
+    def max_attempts(self) -> int: ...
+
+class Outcome:
+    @staticmethod
+    def network_error() -> Outcome: ...
+    @staticmethod
+    def timeout() -> Outcome: ...
+    @staticmethod
+    def rate_limited(retry_after_ms: Optional[int] = None) -> Outcome: ...
+    @staticmethod
+    def blocked() -> Outcome: ...
+    @staticmethod
+    def captcha() -> Outcome: ...
+    @staticmethod
+    def http_status(status: int) -> Outcome: ...
+
+class Decision:
+    @property
+    def action(self) -> Action: ...
+    @property
+    def after_ms(self) -> Optional[int]: ...
+    @property
+    def retry_reason(self) -> Optional[str]: ...
+    @property
+    def fail_reason(self) -> Optional[str]: ...
+    @property
+    def retryable(self) -> bool: ...
+    @property
+    def timeout_ms(self) -> Optional[int]: ...
+
+class DecisionEvent:
+    @property
+    def ctx(self) -> RequestContext: ...
+    @property
+    def outcome(self) -> Outcome: ...
+    @property
+    def auth_decision(self) -> Optional[AuthDecision]: ...
+    @property
+    def refresh_result(self) -> Optional[bool]: ...
+    @property
+    def decision(self) -> Decision: ...
+
+class Client:
+    def __init__(self, policy_json: Optional[str] = None) -> None: ...
+    def set_policy(self, policy_json: str) -> None: ...
+    def policy_json(self) -> str: ...
+    def decide(
+        self,
+        ctx: RequestContext,
+        outcome: Outcome,
+        auth_decision: Optional[AuthDecision] = None,
+        refresh_result: Optional[bool] = None,
+    ) -> Decision: ...
+    def decide_json(self, input: str) -> str: ...
+    def record_attempt(
+        self,
+        latency_ms: int,
+        outcome: Optional[Outcome] = None,
+        partition: Optional[str] = None,
+    ) -> None: ...
+    def recommended_timeout_ms(self, partition: Optional[str] = None) -> int: ...
+    def acquire(self, queued: bool = False, priority: Priority = "Normal") -> Admission: ...
+    def cancel_queued(self) -> None: ...
+    def release(self, latency_ms: int, outcome: Optional[Outcome] = None) -> None: ...
+    def concurrency_limit(self) -> int: ...
+    def plan_hedge(self, ctx: RequestContext, hedges_sent: int = 0) -> Optional[int]: ...
+    def report_hedge_result(self, hedges_sent: int) -> None: ...
+    def reset(self) -> None: ...
+    def reset_auth(self) -> None: ...
+    def state(self) -> dict[str, Any]: ...
+    def export_state(self, now_ms: int) -> str: ...
+    def import_state(self, state: str) -> None: ...
+    def set_explain(self, explain: bool) -> None: ...
+    def last_explanation(self) -> Optional[dict[str, Any]]: ...
+    def set_observer(
+        self, observer: Optional[Callable[[DecisionEvent], object]]
+    ) -> None: ...
+    def metrics(self) -> dict[str, Any]: ...
+    def metrics_prometheus(self) -> str: ...
+    def set_recording(self, recording: bool) -> None: ...
+    def take_recording(self) -> str: ...
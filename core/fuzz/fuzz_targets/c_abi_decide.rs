@@ -0,0 +1,328 @@
+//! Drives one client through the C ABI with arbitrary field values: raw
+//! enum integers, out-of-range numbers and NULL, non-UTF-8 or arbitrary
+//! C strings. Every call must report a status rather than panic, and the
+//! recorded session must replay to the same decisions.
+//!
+//! The structs are built field by field rather than from raw bytes: they
+//! carry C string pointers, and reinterpreted bytes would hand the ABI
+//! wild pointers, which no `tc_*` function can detect.
+
+#![no_main]
+
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    ptr,
+};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use transport_core::{ffi::*, replay};
+
+#[derive(Debug, Arbitrary)]
+struct Context {
+    method: c_int,
+    attempt: u8,
+    max_attempts: u8,
+    allow_non_idempotent_retry: bool,
+    idempotency_key: Option<Vec<u8>>,
+    partition: Option<Vec<u8>>,
+    priority: c_int,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Outcome {
+    kind: c_int,
+    http_status: u16,
+    retry_after_ms: u32,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Call {
+    SetHintMode {
+        mode: c_int,
+        max_jitter_ms: u32,
+        jitter_seed: u64,
+    },
+    SetHedgePolicy {
+        max_hedges: u8,
+        delay_ms: u32,
+    },
+    SetHedgeAdaptation {
+        delay_percentile: u8,
+        min_samples: u32,
+        budget_percent: u32,
+    },
+    SetTimeoutPolicy {
+        percentile: u8,
+        factor_percent: u32,
+        min_ms: u32,
+        max_ms: u32,
+        default_ms: u32,
+        min_samples: u32,
+        window: u32,
+    },
+    SetConcurrencyPolicy {
+        algorithm: c_int,
+        initial_limit: u32,
+        min_limit: u32,
+        max_limit: u32,
+        decrease_percent: u8,
+        max_queue: u32,
+        background_share_percent: u8,
+        vegas_alpha: u32,
+        vegas_beta: u32,
+    },
+    Decide {
+        ctx: Context,
+        outcome: Outcome,
+        auth_decision: c_int,
+        refresh_result: i8,
+    },
+    DecideJson(Vec<u8>),
+    RecordAttempt {
+        partition: Option<Vec<u8>>,
+        latency_ms: u32,
+        outcome: Option<Outcome>,
+    },
+    Acquire {
+        queued: bool,
+        priority: c_int,
+    },
+    CancelQueued,
+    Release {
+        latency_ms: u32,
+        outcome: Option<Outcome>,
+    },
+    PlanHedge {
+        ctx: Context,
+        hedges_sent: u8,
+    },
+    ReportHedgeResult {
+        hedges_sent: u8,
+    },
+    ImportState(Vec<u8>),
+    ResetAuth,
+    Reset,
+}
+
+/// A C string from arbitrary bytes, cut at the first NUL.
+fn c_string(bytes: &Option<Vec<u8>>) -> Option<CString> {
+    bytes.as_ref().map(|bytes| {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        CString::new(&bytes[..end]).expect("cut at the first NUL")
+    })
+}
+
+fn as_ptr(s: &Option<CString>) -> *const c_char {
+    s.as_ref().map_or(ptr::null(), |s| s.as_ptr())
+}
+
+fn outcome_t(outcome: &Outcome) -> tc_outcome_t {
+    tc_outcome_t {
+        kind: outcome.kind,
+        http_status: outcome.http_status,
+        retry_after_ms: outcome.retry_after_ms,
+    }
+}
+
+/// Takes ownership of a string returned by the library.
+fn take_string(s: *mut c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    let owned = unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .expect("the library returns UTF-8")
+        .to_string();
+    tc_string_free(s);
+    Some(owned)
+}
+
+fn check() {
+    assert_ne!(tc_last_status(), tc_status_t::TC_STATUS_PANIC);
+}
+
+fn call(client: *mut transport_core_client, call: &Call) {
+    match call {
+        Call::SetHintMode {
+            mode,
+            max_jitter_ms,
+            jitter_seed,
+        } => tc_client_set_hint_mode(client, *mode, *max_jitter_ms, *jitter_seed),
+        Call::SetHedgePolicy {
+            max_hedges,
+            delay_ms,
+        } => tc_client_set_hedge_policy(client, *max_hedges, *delay_ms),
+        Call::SetHedgeAdaptation {
+            delay_percentile,
+            min_samples,
+            budget_percent,
+        } => tc_client_set_hedge_adaptation(
+            client,
+            *delay_percentile,
+            *min_samples,
+            *budget_percent,
+        ),
+        Call::SetTimeoutPolicy {
+            percentile,
+            factor_percent,
+            min_ms,
+            max_ms,
+            default_ms,
+            min_samples,
+            window,
+        } => {
+            let policy = tc_timeout_policy_t {
+                percentile: *percentile,
+                factor_percent: *factor_percent,
+                min_ms: *min_ms,
+                max_ms: *max_ms,
+                default_ms: *default_ms,
+                min_samples: *min_samples,
+                window: *window,
+            };
+            tc_client_set_timeout_policy(client, &policy);
+        }
+        Call::SetConcurrencyPolicy {
+            algorithm,
+            initial_limit,
+            min_limit,
+            max_limit,
+            decrease_percent,
+            max_queue,
+            background_share_percent,
+            vegas_alpha,
+            vegas_beta,
+        } => {
+            let policy = tc_concurrency_policy_t {
+                algorithm: *algorithm,
+                initial_limit: *initial_limit,
+                min_limit: *min_limit,
+                max_limit: *max_limit,
+                decrease_percent: *decrease_percent,
+                max_queue: *max_queue,
+                background_share_percent: *background_share_percent,
+                vegas_alpha: *vegas_alpha,
+                vegas_beta: *vegas_beta,
+            };
+            tc_client_set_concurrency_policy(client, &policy);
+        }
+        Call::Decide {
+            ctx,
+            outcome,
+            auth_decision,
+            refresh_result,
+        } => {
+            let key = c_string(&ctx.idempotency_key);
+            let partition = c_string(&ctx.partition);
+            let ctx = tc_request_context_t {
+                method: ctx.method,
+                attempt: ctx.attempt,
+                max_attempts: ctx.max_attempts,
+                allow_non_idempotent_retry: ctx.allow_non_idempotent_retry,
+                idempotency_key: as_ptr(&key),
+                partition: as_ptr(&partition),
+                priority: ctx.priority,
+            };
+            tc_decide(
+                client,
+                &ctx,
+                &outcome_t(outcome),
+                *auth_decision,
+                *refresh_result,
+            );
+            check();
+
+            tc_last_retry_after_ms(client);
+            tc_last_timeout_ms(client);
+            tc_last_retry_reason(client);
+            tc_last_fail_reason(client);
+            tc_last_fail_retryable(client);
+            take_string(tc_last_explanation_json(client));
+        }
+        Call::DecideJson(input) => {
+            let input = c_string(&Some(input.clone()));
+            let mut output = ptr::null_mut();
+            tc_decide_json(client, as_ptr(&input), &mut output);
+            check();
+            take_string(output);
+        }
+        Call::RecordAttempt {
+            partition,
+            latency_ms,
+            outcome,
+        } => {
+            let partition = c_string(partition);
+            let outcome = outcome.as_ref().map(outcome_t);
+            tc_record_attempt(
+                client,
+                as_ptr(&partition),
+                *latency_ms,
+                outcome.as_ref().map_or(ptr::null(), |o| o as *const _),
+            );
+        }
+        Call::Acquire { queued, priority } => {
+            tc_acquire(client, *queued, *priority);
+        }
+        Call::CancelQueued => tc_cancel_queued(client),
+        Call::Release {
+            latency_ms,
+            outcome,
+        } => {
+            let outcome = outcome.as_ref().map(outcome_t);
+            tc_release(
+                client,
+                *latency_ms,
+                outcome.as_ref().map_or(ptr::null(), |o| o as *const _),
+            );
+        }
+        Call::PlanHedge { ctx, hedges_sent } => {
+            let key = c_string(&ctx.idempotency_key);
+            let partition = c_string(&ctx.partition);
+            let ctx = tc_request_context_t {
+                method: ctx.method,
+                attempt: ctx.attempt,
+                max_attempts: ctx.max_attempts,
+                allow_non_idempotent_retry: ctx.allow_non_idempotent_retry,
+                idempotency_key: as_ptr(&key),
+                partition: as_ptr(&partition),
+                priority: ctx.priority,
+            };
+            let mut after_ms = 0;
+            tc_plan_hedge(client, &ctx, *hedges_sent, &mut after_ms);
+        }
+        Call::ReportHedgeResult { hedges_sent } => tc_report_hedge_result(client, *hedges_sent),
+        Call::ImportState(state) => {
+            let state = c_string(&Some(state.clone()));
+            tc_client_import_state(client, as_ptr(&state));
+        }
+        Call::ResetAuth => tc_client_reset_auth(client),
+        Call::Reset => tc_client_reset(client),
+    }
+    check();
+}
+
+fuzz_target!(|calls: Vec<Call>| {
+    let client = tc_client_new();
+    tc_client_set_explain(client, true);
+    tc_client_set_recording(client, true);
+
+    for c in &calls {
+        call(client, c);
+    }
+
+    take_string(tc_client_metrics_json(client));
+    take_string(tc_client_export_state(client, 0));
+    let mut state = unsafe { std::mem::zeroed::<tc_client_state_t>() };
+    tc_client_state(client, &mut state);
+    check();
+
+    // Decisions made through `tc_decide_json` are recorded too.
+    let log = take_string(tc_client_take_recording(client)).expect("recording is on");
+    let entries = replay::parse(&log).expect("recordings parse");
+    let report = replay::replay(&entries, None);
+    assert!(report.is_identical(), "{:?}", report.differences);
+
+    tc_client_free(client);
+});
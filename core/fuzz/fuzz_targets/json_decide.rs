@@ -0,0 +1,20 @@
+//! Feeds arbitrary documents to the JSON entry points, stateless and
+//! through a client. Every input must produce a JSON response, either a
+//! decision or an error, and never a panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use transport_core::{client::Client, json};
+
+fuzz_target!(|input: &str| {
+    let response = json::decide_json(input);
+    let response: serde_json::Value = serde_json::from_str(&response).expect("responses are JSON");
+    assert!(response.is_object());
+
+    let mut client = Client::new();
+    for _ in 0..2 {
+        let response = client.decide_json(input);
+        serde_json::from_str::<serde_json::Value>(&response).expect("responses are JSON");
+    }
+});
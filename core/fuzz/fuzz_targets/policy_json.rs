@@ -0,0 +1,15 @@
+//! Parses arbitrary policy documents and, when one parses, runs every
+//! policy-driven subsystem under it: extreme or inconsistent values
+//! (zero limits, inverted bounds, huge jitter) must not panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use transport_core::policy::Policy;
+use transport_core_fuzz::check_policy;
+
+fuzz_target!(|input: &str| {
+    if let Ok(policy) = serde_json::from_str::<Policy>(input) {
+        check_policy(policy);
+    }
+});
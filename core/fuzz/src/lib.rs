@@ -0,0 +1,58 @@
+//! Checks shared by the fuzz targets and `tests/fuzz_regressions.rs`,
+//! which includes this file so crash inputs replay through exactly the
+//! calls the targets make.
+
+use transport_core::{
+    client::Client,
+    model::{HttpMethod, Outcome, Priority, RequestContext},
+    policy::{diff_policies, InputSpace, Policy},
+};
+
+/// Round-trips `policy` through JSON, then runs every policy-driven
+/// subsystem under it.
+pub fn check_policy(policy: Policy) {
+    let round_trip: Policy = serde_json::from_str(&serde_json::to_string(&policy).unwrap())
+        .expect("policies round-trip");
+    assert_eq!(round_trip, policy);
+
+    let space = InputSpace {
+        methods: vec![HttpMethod::GET, HttpMethod::POST],
+        max_attempts: 2,
+        statuses: vec![401, 429, 503],
+        retry_after_ms: vec![0, 1_500, u32::MAX],
+    };
+    diff_policies(&Policy::default(), &policy, &space);
+
+    let mut client = Client::with_policy(policy);
+    let ctx = RequestContext {
+        method: HttpMethod::GET,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: Some("fuzz".to_string()),
+        allow_non_idempotent_retry: false,
+        partition: Some("upstream".to_string()),
+        priority: Priority::Background,
+    };
+
+    for (latency_ms, outcome) in [
+        (0, None),
+        (u32::MAX, Some(Outcome::TimeoutError)),
+        (
+            50,
+            Some(Outcome::RateLimited {
+                retry_after_ms: Some(u32::MAX),
+            }),
+        ),
+        (10, Some(Outcome::NetworkError)),
+    ] {
+        client.acquire(false, ctx.priority);
+        client.record_attempt(ctx.partition.as_deref(), latency_ms, outcome.as_ref());
+        client.release(latency_ms, outcome.as_ref());
+        if let Some(outcome) = outcome {
+            client.decide(&ctx, outcome, None, None);
+        }
+        client.recommended_timeout_ms(ctx.partition.as_deref());
+        client.plan_hedge(&ctx, 0);
+        client.report_hedge_result(1);
+    }
+}
@@ -1,10 +1,12 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AuthDecision {
     RefreshAndRetry,
     Fail,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AuthState {
     pub refresh_in_progress: bool,
     pub refresh_attempted: bool,
@@ -18,3 +20,9 @@ impl AuthState {
         }
     }
 }
+
+impl Default for AuthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,7 @@
+use std::io;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = transport_core::cli::main(&args, &mut io::stdin().lock(), &mut io::stdout().lock());
+    std::process::exit(code);
+}
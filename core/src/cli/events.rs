@@ -0,0 +1,128 @@
+use serde::Deserialize;
+
+use crate::{auth::AuthDecision, model::Outcome};
+
+/// One attempt's result as fed to the simulator.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Event {
+    /// `None` is a success.
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+    #[serde(default)]
+    pub auth_decision: Option<AuthDecision>,
+    #[serde(default)]
+    pub refresh_result: Option<bool>,
+}
+
+impl Event {
+    fn success() -> Self {
+        Self::outcome(None)
+    }
+
+    fn outcome(outcome: Option<Outcome>) -> Self {
+        Self {
+            outcome,
+            auth_decision: None,
+            refresh_result: None,
+        }
+    }
+}
+
+/// Parses one line of input: a JSON object when it starts with `{`,
+/// DSL tokens otherwise.
+pub fn parse_line(line: &str, events: &mut Vec<Event>) -> Result<(), String> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let event = serde_json::from_str(line).map_err(|e| format!("{line}: {e}"))?;
+        events.push(event);
+        return Ok(());
+    }
+
+    for token in line.split_whitespace() {
+        parse_token(token, events)?;
+    }
+    Ok(())
+}
+
+/// Parses a compact DSL token:
+///
+/// - `200`, any 2xx/3xx status or `ok`: success
+/// - `401`: auth failure, refresh requested
+/// - `refresh:ok` / `refresh:fail` / `refresh:deny`: refresh result for
+///   the preceding 401, or the auth provider declining to refresh
+/// - `429` or `429:<ms>`, `rate-limited[:<ms>]`: rate limited, with an
+///   optional Retry-After hint
+/// - 5xx: network error, as `spec/error.md` recommends
+/// - `timeout`, `network`, `blocked`, `captcha`: semantic outcomes
+/// - `http:<status>` or any other status: raw `HttpStatus`
+pub fn parse_token(token: &str, events: &mut Vec<Event>) -> Result<(), String> {
+    if let Some(result) = token.strip_prefix("refresh:") {
+        let Some(last) = events
+            .last_mut()
+            .filter(|e| matches!(e.outcome, Some(Outcome::HttpStatus(401))))
+        else {
+            return Err(format!("{token}: must follow a 401"));
+        };
+
+        match result {
+            "ok" => last.refresh_result = Some(true),
+            "fail" => last.refresh_result = Some(false),
+            "deny" => last.auth_decision = Some(AuthDecision::Fail),
+            _ => {
+                return Err(format!(
+                    "{token}: expected refresh:ok, refresh:fail or refresh:deny"
+                ))
+            }
+        }
+        return Ok(());
+    }
+
+    let (name, arg) = match token.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (token, None),
+    };
+    let hint = || -> Result<Option<u32>, String> {
+        arg.map(|ms| ms.parse().map_err(|_| format!("{token}: invalid delay")))
+            .transpose()
+    };
+
+    let event = match name {
+        "ok" | "success" => Event::success(),
+        "timeout" => Event::outcome(Some(Outcome::TimeoutError)),
+        "network" => Event::outcome(Some(Outcome::NetworkError)),
+        "blocked" => Event::outcome(Some(Outcome::Blocked)),
+        "captcha" => Event::outcome(Some(Outcome::Captcha)),
+        "rate-limited" | "429" => Event::outcome(Some(Outcome::RateLimited {
+            retry_after_ms: hint()?,
+        })),
+        "http" => {
+            let status = arg
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| format!("{token}: expected http:<status>"))?;
+            Event::outcome(Some(Outcome::HttpStatus(status)))
+        }
+        _ => {
+            let status: u16 = name
+                .parse()
+                .map_err(|_| format!("{token}: unknown outcome"))?;
+            if arg.is_some() {
+                return Err(format!("{token}: only 429 takes a delay"));
+            }
+
+            match status {
+                200..=399 => Event::success(),
+                401 => Event {
+                    outcome: Some(Outcome::HttpStatus(401)),
+                    auth_decision: Some(AuthDecision::RefreshAndRetry),
+                    refresh_result: None,
+                },
+                500..=599 => Event::outcome(Some(Outcome::NetworkError)),
+                _ => Event::outcome(Some(Outcome::HttpStatus(status))),
+            }
+        }
+    };
+
+    events.push(event);
+    Ok(())
+}
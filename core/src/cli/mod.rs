@@ -0,0 +1,560 @@
+//! The `transport-core` command-line tool, built with the `cli` feature.
+//!
+//! ```text
+//! transport-core simulate --policy policy.json 401 refresh:ok 503 timeout 200
+//! ```
+//!
+//! Outcomes come from the arguments or, when there are none, from stdin
+//! as DSL tokens or JSON lines (see [`events`]).
+//!
+//! `transport-core load config.json` runs a [`crate::simulation`] and
+//! prints its report.
+//!
+//! `transport-core replay decisions.jsonl` replays a [`crate::replay`]
+//! recording and lists the decisions that changed; it exits with 1 when
+//! there are any.
+//!
+//! `transport-core diff old.json new.json` lists every input two policies
+//! decide differently (see [`crate::policy::diff_policies`]), exiting
+//! with 1 when there are any.
+
+pub mod events;
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{BufRead, Write},
+};
+
+use serde::Serialize;
+
+use crate::{
+    auth::AuthDecision,
+    client::Client,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+    policy::{diff_policies, DiffInput, InputSpace, Policy, PolicyDiff},
+    replay::{self, ReplayReport},
+    simulation::{self, SimulationConfig},
+};
+
+pub use events::Event;
+
+const USAGE: &str = "\
+usage: transport-core simulate [options] [OUTCOME...]
+       transport-core load CONFIG
+       transport-core replay [--policy FILE] [--json] LOG
+       transport-core diff [--max-attempts N] [--json] OLD NEW
+
+simulate: feeds a sequence of attempt outcomes through one client and prints the
+decisions, delays and cumulative time. Reads stdin when no outcomes are
+given; lines starting with `{` are JSON events.
+
+outcomes:
+  200, ok            success (any 2xx/3xx)
+  401                auth failure, refresh requested
+  refresh:ok|fail    result of the refresh for the preceding 401
+  refresh:deny       the auth provider declines to refresh
+  429[:ms]           rate limited, optional Retry-After hint
+  5xx, network       network error
+  timeout            timeout
+  blocked, captcha   hard failures
+  http:<status>      raw HTTP status (legacy fallback)
+
+options:
+  --policy FILE      policy as JSON (default: the v1 policy)
+  --method METHOD    request method (default: GET)
+  --max-attempts N   attempts per request (default: 3)
+  --partition NAME   latency partition
+  --priority P       background, normal or critical (default: normal)
+  --latency-ms N     time each attempt takes (default: 0)
+  --json             print JSON lines instead of a table
+
+load: runs many virtual clients against a modelled upstream, as
+described by a JSON simulation config, and prints the report as JSON.
+
+replay: re-runs the decisions in a recording (JSON lines from
+Client::take_recording) through this engine and lists those that differ,
+exiting with 1 if any do. --policy replays under another policy instead
+of the recorded one.
+
+diff: decides every combination of method, idempotency, attempt, outcome,
+Retry-After hint and auth input under two policies (JSON files; `-` is
+the default policy) and lists those decided differently, exiting with 1
+if any are. --max-attempts sets the attempts tried (default: 3).
+";
+
+/// Settings for [`simulate`].
+#[derive(Debug, Clone)]
+pub struct SimulateOptions {
+    pub policy: Policy,
+    pub method: HttpMethod,
+    pub max_attempts: u8,
+    pub partition: Option<String>,
+    pub priority: Priority,
+    /// Time each attempt takes before its outcome is known.
+    pub latency_ms: u32,
+}
+
+impl Default for SimulateOptions {
+    fn default() -> Self {
+        Self {
+            policy: Policy::default(),
+            method: HttpMethod::GET,
+            max_attempts: 3,
+            partition: None,
+            priority: Priority::Normal,
+            latency_ms: 0,
+        }
+    }
+}
+
+/// One simulated attempt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    /// 1-based request number; a request ends on success or failure.
+    pub request: u32,
+    pub attempt: u8,
+    /// `None` is a success.
+    pub outcome: Option<Outcome>,
+    /// `None` for a success, which needs no decision.
+    pub decision: Option<Decision>,
+    /// Backoff before the next attempt, 0 unless retrying.
+    pub delay_ms: u32,
+    /// Time since the first attempt started, once this attempt's
+    /// outcome is known and its backoff has passed.
+    pub elapsed_ms: u64,
+}
+
+/// Runs `events` through one client as consecutive attempts. Every
+/// request starts at attempt 1 with fresh auth state.
+pub fn simulate(options: &SimulateOptions, events: &[Event]) -> Vec<Row> {
+    let mut client = Client::with_policy(options.policy.clone());
+    let mut rows = Vec::with_capacity(events.len());
+    let mut request = 1;
+    let mut attempt = 1;
+    let mut elapsed_ms = 0;
+
+    for event in events {
+        elapsed_ms += u64::from(options.latency_ms);
+
+        let decision = event.outcome.clone().map(|outcome| {
+            let ctx = RequestContext {
+                method: options.method.clone(),
+                attempt,
+                max_attempts: options.max_attempts,
+                idempotency_key: None,
+                allow_non_idempotent_retry: false,
+                partition: options.partition.clone(),
+                priority: options.priority,
+            };
+            client.decide(&ctx, outcome, event.auth_decision, event.refresh_result)
+        });
+
+        let delay_ms = match decision {
+            Some(Decision::Retry { after_ms, .. })
+            | Some(Decision::RefreshAndRetry { after_ms }) => Some(after_ms),
+            _ => None,
+        };
+        elapsed_ms += u64::from(delay_ms.unwrap_or(0));
+
+        rows.push(Row {
+            request,
+            attempt,
+            outcome: event.outcome.clone(),
+            decision,
+            delay_ms: delay_ms.unwrap_or(0),
+            elapsed_ms,
+        });
+
+        if delay_ms.is_some() {
+            attempt = attempt.saturating_add(1);
+        } else {
+            request += 1;
+            attempt = 1;
+            client.reset_auth();
+        }
+    }
+
+    rows
+}
+
+/// Short name of an outcome, in the DSL's terms.
+pub fn outcome_label(outcome: Option<&Outcome>) -> String {
+    match outcome {
+        None => "ok".to_string(),
+        Some(Outcome::NetworkError) => "network".to_string(),
+        Some(Outcome::TimeoutError) => "timeout".to_string(),
+        Some(Outcome::RateLimited {
+            retry_after_ms: Some(ms),
+        }) => format!("429:{ms}"),
+        Some(Outcome::RateLimited {
+            retry_after_ms: None,
+        }) => "429".to_string(),
+        Some(Outcome::Blocked) => "blocked".to_string(),
+        Some(Outcome::Captcha) => "captcha".to_string(),
+        Some(Outcome::HttpStatus(401)) => "401".to_string(),
+        Some(Outcome::HttpStatus(status)) => format!("http:{status}"),
+    }
+}
+
+pub fn decision_label(decision: Option<&Decision>) -> String {
+    match decision {
+        None => "success".to_string(),
+        Some(Decision::Proceed) => "PROCEED".to_string(),
+        Some(Decision::Retry { reason, .. }) => format!("RETRY ({reason:?})"),
+        Some(Decision::RefreshAndRetry { .. }) => "REFRESH_AND_RETRY".to_string(),
+        Some(Decision::Fail {
+            reason,
+            retryable: true,
+        }) => format!("FAIL ({reason:?}, retryable)"),
+        Some(Decision::Fail { reason, .. }) => format!("FAIL ({reason:?})"),
+    }
+}
+
+/// Renders rows as an aligned table followed by a summary line.
+pub fn render_table(rows: &[Row]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>4} {:>4}  {:<14} {:<32} {:>9} {:>11}",
+        "req", "att", "outcome", "decision", "delay_ms", "elapsed_ms"
+    )
+    .unwrap();
+
+    for row in rows {
+        writeln!(
+            out,
+            "{:>4} {:>4}  {:<14} {:<32} {:>9} {:>11}",
+            row.request,
+            row.attempt,
+            outcome_label(row.outcome.as_ref()),
+            decision_label(row.decision.as_ref()),
+            row.delay_ms,
+            row.elapsed_ms
+        )
+        .unwrap();
+    }
+
+    let requests = rows.iter().filter(|r| finishes_request(r)).count();
+    let succeeded = rows.iter().filter(|r| r.decision.is_none()).count();
+    let total_ms = rows.last().map_or(0, |r| r.elapsed_ms);
+    writeln!(
+        out,
+        "\n{requests} request(s) finished, {succeeded} succeeded, {} attempt(s), {total_ms} ms",
+        rows.len()
+    )
+    .unwrap();
+
+    out
+}
+
+fn finishes_request(row: &Row) -> bool {
+    !matches!(
+        row.decision,
+        Some(Decision::Retry { .. }) | Some(Decision::RefreshAndRetry { .. })
+    )
+}
+
+/// Error from parsing arguments or input, printed with the usage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError(pub String);
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self(message)
+    }
+}
+
+/// Entry point of the binary; returns the exit code.
+pub fn main(args: &[String], stdin: &mut dyn BufRead, stdout: &mut dyn Write) -> i32 {
+    match run(args, stdin, stdout) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("transport-core: {err}\n\n{USAGE}");
+            2
+        }
+    }
+}
+
+fn run(args: &[String], stdin: &mut dyn BufRead, stdout: &mut dyn Write) -> Result<i32, CliError> {
+    match args.first().map(String::as_str) {
+        Some("simulate") => simulate_command(&args[1..], stdin, stdout).map(|()| 0),
+        Some("load") => load_command(&args[1..], stdout).map(|()| 0),
+        Some("replay") => replay_command(&args[1..], stdout),
+        Some("diff") => diff_command(&args[1..], stdout),
+        Some("-h" | "--help") => {
+            write!(stdout, "{USAGE}").map_err(io_error)?;
+            Ok(0)
+        }
+        Some(other) => Err(CliError(format!("unknown command `{other}`"))),
+        None => Err(CliError("missing command".to_string())),
+    }
+}
+
+fn simulate_command(
+    args: &[String],
+    stdin: &mut dyn BufRead,
+    stdout: &mut dyn Write,
+) -> Result<(), CliError> {
+    let mut options = SimulateOptions::default();
+    let mut json = false;
+    let mut events = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| CliError(format!("{name} needs a value")))
+        };
+
+        match arg.as_str() {
+            "--policy" => options.policy = load_policy(&value("--policy")?)?,
+            "--method" => options.method = parse_enum(&value("--method")?.to_uppercase())?,
+            "--max-attempts" => options.max_attempts = parse_number(&value("--max-attempts")?)?,
+            "--partition" => options.partition = Some(value("--partition")?),
+            "--priority" => options.priority = parse_priority(&value("--priority")?)?,
+            "--latency-ms" => options.latency_ms = parse_number(&value("--latency-ms")?)?,
+            "--json" => json = true,
+            flag if flag.starts_with("--") => {
+                return Err(CliError(format!("unknown option `{flag}`")))
+            }
+            token => events::parse_token(token, &mut events)?,
+        }
+    }
+
+    if events.is_empty() {
+        for line in stdin.lines() {
+            events::parse_line(&line.map_err(io_error)?, &mut events)?;
+        }
+    }
+
+    let rows = simulate(&options, &events);
+    if json {
+        for row in &rows {
+            writeln!(stdout, "{}", serde_json::to_string(row).unwrap()).map_err(io_error)?;
+        }
+    } else {
+        write!(stdout, "{}", render_table(&rows)).map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn load_command(args: &[String], stdout: &mut dyn Write) -> Result<(), CliError> {
+    let [path] = args else {
+        return Err(CliError("load takes one config file".to_string()));
+    };
+    let raw = fs::read_to_string(path).map_err(|e| CliError(format!("{path}: {e}")))?;
+    let config: SimulationConfig =
+        serde_json::from_str(&raw).map_err(|e| CliError(format!("{path}: {e}")))?;
+
+    writeln!(stdout, "{}", simulation::simulate(&config).to_json()).map_err(io_error)
+}
+
+fn replay_command(args: &[String], stdout: &mut dyn Write) -> Result<i32, CliError> {
+    let mut policy = None;
+    let mut json = false;
+    let mut path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--policy" => {
+                let file = args
+                    .next()
+                    .ok_or_else(|| CliError("--policy needs a value".to_string()))?;
+                policy = Some(load_policy(file)?);
+            }
+            "--json" => json = true,
+            flag if flag.starts_with("--") => {
+                return Err(CliError(format!("unknown option `{flag}`")))
+            }
+            file if path.is_none() => path = Some(file),
+            _ => return Err(CliError("replay takes one log file".to_string())),
+        }
+    }
+
+    let path = path.ok_or_else(|| CliError("replay needs a log file".to_string()))?;
+    let raw = fs::read_to_string(path).map_err(|e| CliError(format!("{path}: {e}")))?;
+    let entries = replay::parse(&raw).map_err(|e| CliError(format!("{path}: {e}")))?;
+
+    let report = replay::replay(&entries, policy.as_ref());
+    if json {
+        writeln!(stdout, "{}", report.to_json()).map_err(io_error)?;
+    } else {
+        write!(stdout, "{}", render_replay(&report)).map_err(io_error)?;
+    }
+    Ok(if report.is_identical() { 0 } else { 1 })
+}
+
+/// Renders a replay report as one line per difference followed by a
+/// summary line.
+pub fn render_replay(report: &ReplayReport) -> String {
+    let mut out = String::new();
+    for difference in &report.differences {
+        writeln!(
+            out,
+            "entry {}: attempt {}/{} {:?} {}: recorded {}, replayed {}",
+            difference.entry,
+            difference.ctx.attempt,
+            difference.ctx.max_attempts,
+            difference.ctx.method,
+            outcome_label(Some(&difference.outcome)),
+            decision_with_delay(&difference.recorded),
+            decision_with_delay(&difference.replayed),
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "{} decision(s) recorded by {} replayed on {}: {} difference(s)",
+        report.decisions,
+        report.engine_versions.join(", "),
+        env!("CARGO_PKG_VERSION"),
+        report.differences.len()
+    )
+    .unwrap();
+    out
+}
+
+fn decision_with_delay(decision: &Decision) -> String {
+    let label = decision_label(Some(decision));
+    match decision {
+        Decision::Retry { after_ms, .. } | Decision::RefreshAndRetry { after_ms } => {
+            format!("{label} after {after_ms} ms")
+        }
+        _ => label,
+    }
+}
+
+fn diff_command(args: &[String], stdout: &mut dyn Write) -> Result<i32, CliError> {
+    let mut space = InputSpace::default();
+    let mut json = false;
+    let mut paths = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-attempts" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError("--max-attempts needs a value".to_string()))?;
+                space.max_attempts = parse_number(value)?;
+            }
+            "--json" => json = true,
+            flag if flag.starts_with("--") => {
+                return Err(CliError(format!("unknown option `{flag}`")))
+            }
+            path => paths.push(path),
+        }
+    }
+
+    let [old, new] = paths[..] else {
+        return Err(CliError("diff takes two policy files".to_string()));
+    };
+    let load = |path: &str| match path {
+        "-" => Ok(Policy::default()),
+        path => load_policy(path),
+    };
+
+    let diff = diff_policies(&load(old)?, &load(new)?, &space);
+    if json {
+        writeln!(stdout, "{}", diff.to_json()).map_err(io_error)?;
+    } else {
+        write!(stdout, "{}", render_diff(&diff)).map_err(io_error)?;
+    }
+    Ok(if diff.is_empty() { 0 } else { 1 })
+}
+
+/// Renders a policy diff as one line per differing input followed by a
+/// summary line.
+pub fn render_diff(diff: &PolicyDiff) -> String {
+    let mut out = String::new();
+    for difference in &diff.differences {
+        writeln!(
+            out,
+            "{}: old {}, new {}",
+            input_label(&difference.input),
+            decision_with_delay(&difference.old),
+            decision_with_delay(&difference.new),
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "{} of {} input(s) decided differently",
+        diff.differences.len(),
+        diff.inputs
+    )
+    .unwrap();
+    out
+}
+
+fn input_label(input: &DiffInput) -> String {
+    let ctx = &input.ctx;
+    let mut label = format!(
+        "{:?} attempt {}/{}",
+        ctx.method, ctx.attempt, ctx.max_attempts
+    );
+    if ctx.idempotency_key.is_some() {
+        label.push_str(" with key");
+    }
+    if ctx.allow_non_idempotent_retry {
+        label.push_str(" allowing retry");
+    }
+    write!(label, " {}", outcome_label(Some(&input.outcome))).unwrap();
+
+    if input.auth_state.refresh_attempted {
+        label.push_str(" after refresh");
+    }
+    match input.auth_decision {
+        Some(AuthDecision::RefreshAndRetry) => label.push_str(" refresh"),
+        Some(AuthDecision::Fail) => label.push_str(" refresh:deny"),
+        None => {}
+    }
+    match input.refresh_result {
+        Some(true) => label.push_str(" refresh:ok"),
+        Some(false) => label.push_str(" refresh:fail"),
+        None => {}
+    }
+    label
+}
+
+fn load_policy(path: &str) -> Result<Policy, CliError> {
+    let raw = fs::read_to_string(path).map_err(|e| CliError(format!("{path}: {e}")))?;
+    serde_json::from_str(&raw).map_err(|e| CliError(format!("{path}: {e}")))
+}
+
+fn parse_enum<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, CliError> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| CliError(format!("unknown value `{name}`")))
+}
+
+fn parse_priority(name: &str) -> Result<Priority, CliError> {
+    match name {
+        "background" => Ok(Priority::Background),
+        "normal" => Ok(Priority::Normal),
+        "critical" => Ok(Priority::Critical),
+        _ => Err(CliError(format!("unknown priority `{name}`"))),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError(format!("invalid number `{value}`")))
+}
+
+fn io_error(err: std::io::Error) -> CliError {
+    CliError(err.to_string())
+}
@@ -0,0 +1,450 @@
+mod state;
+
+pub use state::{ExportedState, StateError, STATE_VERSION};
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::{
+    auth::{AuthDecision, AuthState},
+    concurrency::{Admission, ConcurrencyLimiter, ConcurrencyPolicy},
+    decision::{decide_traced, shed_retry, Explanation, Step, Trace},
+    hedge::{plan_hedge_with_stats, HedgeStats},
+    json,
+    metrics::{DecisionMetrics, MetricsSnapshot},
+    model::{Decision, Outcome, Priority, RequestContext},
+    observer::{DecisionEvent, Observer, ObserverSlot},
+    policy::Policy,
+    replay::{RecordedDecision, Recorder},
+    stats::LatencyStats,
+};
+
+/// Point-in-time view of a [`Client`]'s state, for diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClientSnapshot {
+    pub auth: AuthState,
+    pub concurrency_limit: u32,
+    pub in_flight: u32,
+    pub queued: u32,
+    /// Named latency partitions seen so far (the default one excluded).
+    pub partition_count: usize,
+    pub hedge_stats: HedgeStats,
+}
+
+/// Engine state for one host-side client.
+///
+/// `decide` and friends are the free functions with the state they need
+/// kept in one place, so hosts do not have to thread it themselves.
+#[derive(Debug, Clone, Default)]
+pub struct Client {
+    policy: Policy,
+    auth_state: AuthState,
+    hedge_stats: HedgeStats,
+    latency: LatencyStats,
+    limiter: ConcurrencyLimiter,
+    last_decision: Option<Decision>,
+    last_timeout_ms: Option<u32>,
+    explain: bool,
+    last_explanation: Option<Explanation>,
+    observer: ObserverSlot,
+    metrics: DecisionMetrics,
+    recorder: Recorder,
+    jitter_seed: JitterSeed,
+}
+
+/// Seed for this client's retry jitter, used unless the policy fixes one.
+#[derive(Debug, Clone, Copy)]
+struct JitterSeed(u64);
+
+impl Default for JitterSeed {
+    /// `RandomState` keys differ per instance and per process, so every
+    /// client gets its own seed.
+    fn default() -> Self {
+        Self(RandomState::new().build_hasher().finish())
+    }
+}
+
+impl Client {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy(policy: Policy) -> Self {
+        Self {
+            limiter: ConcurrencyLimiter::new(&policy.concurrency),
+            policy,
+            ..Self::default()
+        }
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    pub fn policy_mut(&mut self) -> &mut Policy {
+        &mut self.policy
+    }
+
+    /// The policy decisions are made under: the configured one, with this
+    /// client's own jitter seed unless it fixes one.
+    fn seeded_policy(&self) -> Policy {
+        let mut policy = self.policy.clone();
+        policy.retry.jitter_seed.get_or_insert(self.jitter_seed.0);
+        policy
+    }
+
+    /// Replaces the concurrency policy and restarts limit adaptation.
+    pub fn set_concurrency_policy(&mut self, policy: ConcurrencyPolicy) {
+        self.limiter.reset_limit(&policy);
+        self.policy.concurrency = policy;
+    }
+
+    pub fn auth_state(&self) -> &AuthState {
+        &self.auth_state
+    }
+
+    /// Clears the auth coordination state, e.g. after the host obtained
+    /// new credentials out of band, so a later 401 may refresh again.
+    pub fn reset_auth(&mut self) {
+        self.auth_state = AuthState::new();
+        self.recorder.record_auth(&self.auth_state);
+        trace_event!(debug, "auth state reset");
+    }
+
+    /// Forgets everything the client has learned: auth state, latency
+    /// samples, hedge totals, the adapted concurrency limit, metrics and
+    /// the last decision. The policy, jitter seed, observer, explanation
+    /// and recording settings are kept, as are the in-flight, queued and
+    /// pending hedge counts so that
+    /// outstanding slots can still be released.
+    pub fn reset(&mut self) {
+        let mut limiter = self.limiter.clone();
+        limiter.reset_limit(&self.policy.concurrency);
+
+        *self = Self {
+            policy: self.policy.clone(),
+            limiter,
+            explain: self.explain,
+            observer: self.observer.clone(),
+            recorder: std::mem::take(&mut self.recorder),
+            jitter_seed: self.jitter_seed,
+            hedge_stats: HedgeStats {
+                hedges_pending: self.hedge_stats.hedges_pending,
+                ..HedgeStats::default()
+            },
+            ..Self::default()
+        };
+        self.recorder.record_auth(&self.auth_state);
+        trace_event!(debug, "client reset");
+    }
+
+    /// Exports the learned state; `now_ms` is the host's clock.
+    pub fn export_state(&self, now_ms: u64) -> ExportedState {
+        ExportedState {
+            version: STATE_VERSION,
+            exported_at_ms: now_ms,
+            auth: self.auth_state.clone(),
+            latency: self.latency.clone(),
+            hedge_stats: self.hedge_stats.clone(),
+            limiter: self.limiter.clone(),
+        }
+    }
+
+    /// Replaces the learned state with an exported one. The policy and
+    /// this client's in-flight, queued and pending hedge counts are kept.
+    /// The imported concurrency limit is clamped to the policy's bounds
+    /// and latency samples are cut to its window.
+    pub fn import_state(&mut self, state: ExportedState) -> Result<(), StateError> {
+        if state.version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(state.version));
+        }
+
+        // The document may come from a client with another policy, or
+        // from anywhere at all.
+        let mut limiter = state.limiter;
+        limiter.keep_slots_of(&self.limiter);
+        limiter.clamp_to(&self.policy.concurrency);
+        let mut latency = state.latency;
+        latency.trim_to(&self.policy.timeout);
+
+        self.auth_state = state.auth;
+        self.latency = latency;
+        self.hedge_stats = HedgeStats {
+            hedges_pending: self.hedge_stats.hedges_pending,
+            ..state.hedge_stats
+        };
+        self.limiter = limiter;
+        self.last_decision = None;
+        self.last_timeout_ms = None;
+        self.last_explanation = None;
+        self.recorder.record_auth(&self.auth_state);
+        trace_event!(
+            debug,
+            exported_at_ms = state.exported_at_ms,
+            concurrency_limit = self.limiter.limit(),
+            "client state imported"
+        );
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> ClientSnapshot {
+        ClientSnapshot {
+            auth: self.auth_state.clone(),
+            concurrency_limit: self.limiter.limit(),
+            in_flight: self.limiter.in_flight(),
+            queued: self.limiter.queued(),
+            partition_count: self.latency.partition_count(),
+            hedge_stats: self.hedge_stats.clone(),
+        }
+    }
+
+    pub fn last_decision(&self) -> Option<&Decision> {
+        self.last_decision.as_ref()
+    }
+
+    pub fn decide(
+        &mut self,
+        ctx: &RequestContext,
+        outcome: Outcome,
+        auth_decision: Option<AuthDecision>,
+        refresh_result: Option<bool>,
+    ) -> Decision {
+        // Only clone the outcome when someone will look at it.
+        let observed_outcome = self.observer.is_set().then(|| outcome.clone());
+        let recorded_outcome = self.recorder.is_on().then(|| outcome.clone());
+        let retry_after_ms = match outcome {
+            Outcome::RateLimited { retry_after_ms } => retry_after_ms,
+            _ => None,
+        };
+
+        let policy = self.seeded_policy();
+        let mut trace = Trace::enabled(self.explain);
+        let decision = decide_traced(
+            ctx,
+            outcome,
+            auth_decision,
+            &mut self.auth_state,
+            refresh_result,
+            &policy,
+            &mut trace,
+        );
+
+        let overloaded = self
+            .limiter
+            .would_reject(&self.policy.concurrency, ctx.priority);
+        let retry = matches!(decision, Decision::Retry { .. });
+        let decision = shed_retry(ctx, decision, overloaded);
+
+        if retry && matches!(decision, Decision::Fail { .. }) {
+            trace_event!(debug, priority = ?ctx.priority, "retry shed under load");
+        }
+        if retry {
+            trace.push(|| Step::LoadShedding {
+                priority: ctx.priority,
+                overloaded,
+                shed: matches!(decision, Decision::Fail { .. }),
+            });
+        }
+
+        self.last_explanation = trace.finish(&decision);
+
+        self.last_timeout_ms = match decision {
+            Decision::Retry { .. } | Decision::RefreshAndRetry { .. } => {
+                Some(self.recommended_timeout_ms(ctx.partition.as_deref()))
+            }
+            _ => None,
+        };
+        self.last_decision = Some(decision.clone());
+        self.metrics
+            .record(ctx, retry_after_ms, refresh_result, &decision);
+
+        if let Some(outcome) = recorded_outcome {
+            // Recorded with the seed, so that replay jitters the same.
+            self.recorder.record_decision(
+                &policy,
+                RecordedDecision {
+                    ctx: ctx.clone(),
+                    outcome,
+                    auth_decision,
+                    refresh_result,
+                    overloaded,
+                    decision: decision.clone(),
+                },
+            );
+        }
+
+        if let Some(outcome) = &observed_outcome {
+            self.observer.notify(&DecisionEvent {
+                ctx,
+                outcome,
+                auth_decision: auth_decision.as_ref(),
+                refresh_result,
+                decision: &decision,
+                timeout_ms: self.last_timeout_ms,
+            });
+        }
+
+        decision
+    }
+
+    /// Counters and histograms over every decision made so far.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Turns recording of `decide` calls on or off (off by default). See
+    /// [`crate::replay`] for the format and how to replay it.
+    pub fn set_recording(&mut self, recording: bool) {
+        match (recording, self.recorder.is_on()) {
+            (true, false) => {
+                let policy = self.seeded_policy();
+                self.recorder.start(&policy, &self.auth_state);
+            }
+            (false, true) => self.recorder.stop(),
+            _ => {}
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_on()
+    }
+
+    /// Drains the JSON lines recorded since the last call. Concatenated,
+    /// the chunks form one replayable log.
+    pub fn take_recording(&mut self) -> String {
+        self.recorder.take()
+    }
+
+    /// Calls `observer` after every decision, replacing any previous one.
+    pub fn set_observer(&mut self, observer: Arc<dyn Observer>) {
+        self.observer.set(Some(observer));
+    }
+
+    pub fn clear_observer(&mut self) {
+        self.observer.set(None);
+    }
+
+    /// Turns decision explanations on or off (off by default). While on,
+    /// each `decide` records the rules it evaluated.
+    pub fn set_explain(&mut self, explain: bool) {
+        self.explain = explain;
+        if !explain {
+            self.last_explanation = None;
+        }
+    }
+
+    /// Explanation of the last `decide` call, if explanations were on.
+    pub fn last_explanation(&self) -> Option<&Explanation> {
+        self.last_explanation.as_ref()
+    }
+
+    /// JSON-in/JSON-out variant of [`Client::decide`] for scripting
+    /// hosts; see [`json::decide_json`] for the format. The request's
+    /// `auth_state` and `policy` fields are ignored in favor of the
+    /// client's own.
+    pub fn decide_json(&mut self, input: &str) -> String {
+        json::decide_with_client(self, input).to_json()
+    }
+
+    /// Per-attempt timeout recommended for the retry returned by the last
+    /// `decide` call, or `None` if the last decision was not a retry.
+    pub fn last_timeout_ms(&self) -> Option<u32> {
+        self.last_timeout_ms
+    }
+
+    /// Reports one finished attempt. `outcome` is `None` for a success.
+    pub fn record_attempt(
+        &mut self,
+        partition: Option<&str>,
+        latency_ms: u32,
+        outcome: Option<&Outcome>,
+    ) {
+        self.latency
+            .record(partition, latency_ms, outcome, &self.policy.timeout);
+        trace_event!(trace, partition, latency_ms, outcome = ?outcome, "attempt recorded");
+    }
+
+    pub fn recommended_timeout_ms(&self, partition: Option<&str>) -> u32 {
+        self.latency
+            .recommended_timeout_ms(partition, &self.policy.timeout)
+    }
+
+    pub fn latency_stats(&self) -> &LatencyStats {
+        &self.latency
+    }
+
+    /// See [`plan_hedge_with_stats`]: the delay follows this client's
+    /// latencies and the budget its hedges. `hedges_sent` counts hedges
+    /// already sent for this request. A planned hedge counts against the
+    /// budget until the request is reported, whether sent or not.
+    pub fn plan_hedge(&mut self, ctx: &RequestContext, hedges_sent: u8) -> Option<u32> {
+        let delay_ms = plan_hedge_with_stats(
+            ctx,
+            hedges_sent,
+            &self.policy.hedge,
+            &self.latency,
+            &self.hedge_stats,
+        )?;
+        self.hedge_stats.plan();
+        Some(delay_ms)
+    }
+
+    /// Reports how many hedges a finished request sent, which settles
+    /// the hedges planned for it.
+    pub fn report_hedge_result(&mut self, hedges_sent: u8) {
+        self.hedge_stats.record(hedges_sent);
+        trace_event!(trace, hedges_sent, "hedge result recorded");
+    }
+
+    pub fn hedge_stats(&self) -> &HedgeStats {
+        &self.hedge_stats
+    }
+
+    /// Asks for an in-flight slot before sending a request. `queued` is
+    /// true when retrying after an earlier `Admission::Queued`.
+    pub fn acquire(&mut self, queued: bool, priority: Priority) -> Admission {
+        let admission = self
+            .limiter
+            .acquire(&self.policy.concurrency, queued, priority);
+        trace_event!(
+            trace,
+            admission = ?admission,
+            priority = ?priority,
+            in_flight = self.limiter.in_flight(),
+            limit = self.limiter.limit(),
+            "slot requested"
+        );
+        admission
+    }
+
+    /// Gives up a queue slot from an earlier `Admission::Queued`.
+    pub fn cancel_queued(&mut self) {
+        self.limiter.cancel_queued();
+    }
+
+    /// Releases a slot from `Admission::Acquired` once the request
+    /// finished. `outcome` is `None` for a success.
+    pub fn release(&mut self, latency_ms: u32, outcome: Option<&Outcome>) {
+        #[cfg(feature = "tracing")]
+        let before = self.limiter.limit();
+
+        self.limiter
+            .release(&self.policy.concurrency, latency_ms, outcome);
+
+        #[cfg(feature = "tracing")]
+        if self.limiter.limit() != before {
+            tracing::debug!(
+                from = before,
+                to = self.limiter.limit(),
+                "concurrency limit changed"
+            );
+        }
+    }
+
+    pub fn concurrency(&self) -> &ConcurrencyLimiter {
+        &self.limiter
+    }
+}
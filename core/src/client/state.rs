@@ -0,0 +1,76 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::AuthState, concurrency::ConcurrencyLimiter, hedge::HedgeStats, stats::LatencyStats,
+};
+
+/// Version of the [`ExportedState`] document format.
+pub const STATE_VERSION: u32 = 1;
+
+/// Everything a [`Client`](super::Client) has learned, as a versioned
+/// document a host can persist between short-lived processes.
+///
+/// The policy is not included: the importing client keeps its own, and
+/// imported state is fitted to it. Nor are in-flight, queued and pending
+/// hedge counts, which belong to the process holding the slots.
+///
+/// None of the learned state is timestamped, so there is nothing to
+/// rebase. `exported_at_ms` records the host's clock at export and is
+/// not read by the engine; hosts use it to discard documents they
+/// consider too old.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedState {
+    pub version: u32,
+    pub exported_at_ms: u64,
+    pub auth: AuthState,
+    pub latency: LatencyStats,
+    pub hedge_stats: HedgeStats,
+    pub limiter: ConcurrencyLimiter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The document is not valid JSON for [`ExportedState`].
+    Malformed(String),
+    /// The document was written by an incompatible version.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Malformed(err) => write!(f, "malformed state: {err}"),
+            StateError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported state version {version} (expected {STATE_VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl ExportedState {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("state serializes to JSON")
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, StateError> {
+        // Check the version first so a newer document reports that
+        // rather than whichever field changed shape.
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+
+        let Version { version } =
+            serde_json::from_str(input).map_err(|err| StateError::Malformed(err.to_string()))?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+
+        serde_json::from_str(input).map_err(|err| StateError::Malformed(err.to_string()))
+    }
+}
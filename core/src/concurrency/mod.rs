@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Outcome, Priority};
+
+/// How the concurrency limit reacts to host-reported results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LimitAlgorithm {
+    /// Additive increase, multiplicative decrease on rate limiting and
+    /// timeouts.
+    #[default]
+    Aimd,
+    /// AIMD on overload signals, plus latency-based adjustment: the limit
+    /// shrinks as latency rises above the lowest latency observed.
+    Vegas,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConcurrencyPolicy {
+    pub algorithm: LimitAlgorithm,
+    pub initial_limit: u32,
+    pub min_limit: u32,
+    pub max_limit: u32,
+    /// Share of the limit removed on an overload signal, in percent.
+    pub decrease_percent: u8,
+    /// Requests allowed to wait for a slot once the limit is reached.
+    pub max_queue: u32,
+    /// Share of the limit, in percent, that `Priority::Background`
+    /// requests may occupy. Beyond it they are rejected, never queued.
+    pub background_share_percent: u8,
+    /// Vegas: estimated queued requests below which the limit grows.
+    pub vegas_alpha: u32,
+    /// Vegas: estimated queued requests above which the limit shrinks.
+    pub vegas_beta: u32,
+}
+
+impl Default for ConcurrencyPolicy {
+    fn default() -> Self {
+        Self {
+            algorithm: LimitAlgorithm::Aimd,
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 200,
+            decrease_percent: 10,
+            max_queue: 0,
+            background_share_percent: 80,
+            vegas_alpha: 3,
+            vegas_beta: 6,
+        }
+    }
+}
+
+/// Result of asking for an in-flight slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Admission {
+    /// The request may start now. The host MUST call `release` later.
+    Acquired,
+    /// The limit is reached; the request holds a queue slot and SHOULD
+    /// ask again (as queued) once another request is released.
+    Queued,
+    /// The limit and the queue are full; the request SHOULD NOT be sent.
+    Rejected,
+}
+
+/// Adaptive limit on in-flight requests.
+///
+/// The engine only counts; the host reports when requests start and
+/// finish and does the actual waiting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyLimiter {
+    limit: u32,
+    in_flight: u32,
+    queued: u32,
+    successes: u32,
+    min_latency_ms: Option<u32>,
+}
+
+impl Default for ConcurrencyLimiter {
+    fn default() -> Self {
+        Self::new(&ConcurrencyPolicy::default())
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(policy: &ConcurrencyPolicy) -> Self {
+        let mut limiter = Self {
+            limit: 0,
+            in_flight: 0,
+            queued: 0,
+            successes: 0,
+            min_latency_ms: None,
+        };
+        limiter.reset_limit(policy);
+        limiter
+    }
+
+    /// Restarts adaptation from `policy.initial_limit`, keeping the
+    /// in-flight and queued counts.
+    pub fn reset_limit(&mut self, policy: &ConcurrencyPolicy) {
+        self.limit = clamp_limit(policy.initial_limit, policy);
+        self.successes = 0;
+        self.min_latency_ms = None;
+    }
+
+    /// Brings a limit adapted under another policy, e.g. an imported
+    /// one, within this policy's bounds.
+    pub(crate) fn clamp_to(&mut self, policy: &ConcurrencyPolicy) {
+        self.limit = clamp_limit(self.limit, policy);
+    }
+
+    /// Takes the in-flight and queued counts from `other`, which owns
+    /// the slots the host is actually holding.
+    pub(crate) fn keep_slots_of(&mut self, other: &ConcurrencyLimiter) {
+        self.in_flight = other.in_flight;
+        self.queued = other.queued;
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn in_flight(&self) -> u32 {
+        self.in_flight
+    }
+
+    pub fn queued(&self) -> u32 {
+        self.queued
+    }
+
+    /// Asks for an in-flight slot. `queued` is true when the request
+    /// already holds a queue slot from an earlier `Admission::Queued`.
+    ///
+    /// Queued requests are served first: a new request only gets a slot
+    /// left free after every queued one. `Normal` requests queue up to
+    /// `max_queue` and `Critical` requests up to one limit's worth past
+    /// it, while `Background` requests are rejected as soon as they would
+    /// exceed their share of the limit.
+    pub fn acquire(
+        &mut self,
+        policy: &ConcurrencyPolicy,
+        queued: bool,
+        priority: Priority,
+    ) -> Admission {
+        let admission = self.admission(policy, queued, priority);
+
+        match admission {
+            Admission::Acquired => {
+                if queued {
+                    self.queued = self.queued.saturating_sub(1);
+                }
+                self.in_flight += 1;
+            }
+            Admission::Queued if !queued => self.queued += 1,
+            Admission::Queued | Admission::Rejected => {}
+        }
+
+        admission
+    }
+
+    /// True if a request of this priority would be rejected right now.
+    /// Used to shed retries before they are sent.
+    pub fn would_reject(&self, policy: &ConcurrencyPolicy, priority: Priority) -> bool {
+        self.admission(policy, false, priority) == Admission::Rejected
+    }
+
+    fn admission(&self, policy: &ConcurrencyPolicy, queued: bool, priority: Priority) -> Admission {
+        let limit = match priority {
+            Priority::Background => {
+                let share = u64::from(policy.background_share_percent.min(100));
+                // At most `limit`, so the narrowing cannot fail.
+                u32::try_from(u64::from(self.limit) * share / 100).unwrap_or(self.limit)
+            }
+            Priority::Normal | Priority::Critical => self.limit,
+        };
+
+        // Slots are held for the requests already waiting, so new
+        // arrivals cannot starve them.
+        let waiting = if queued { 0 } else { self.queued };
+        if u64::from(self.in_flight) + u64::from(waiting) < u64::from(limit) {
+            return Admission::Acquired;
+        }
+
+        if queued {
+            return Admission::Queued;
+        }
+
+        let queued = u64::from(self.queued);
+        match priority {
+            Priority::Background => Admission::Rejected,
+            Priority::Normal if queued >= u64::from(policy.max_queue) => Admission::Rejected,
+            Priority::Critical if queued >= u64::from(policy.max_queue) + u64::from(self.limit) => {
+                Admission::Rejected
+            }
+            Priority::Normal | Priority::Critical => Admission::Queued,
+        }
+    }
+
+    /// Gives up a queue slot without ever acquiring.
+    pub fn cancel_queued(&mut self) {
+        self.queued = self.queued.saturating_sub(1);
+    }
+
+    /// Releases an in-flight slot. `outcome` is `None` for a success.
+    pub fn release(
+        &mut self,
+        policy: &ConcurrencyPolicy,
+        latency_ms: u32,
+        outcome: Option<&Outcome>,
+    ) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+
+        let overloaded = matches!(
+            outcome,
+            Some(Outcome::RateLimited { .. }) | Some(Outcome::TimeoutError)
+        );
+
+        if overloaded {
+            self.decrease(policy);
+            return;
+        }
+
+        if outcome.is_some() {
+            return;
+        }
+
+        match policy.algorithm {
+            LimitAlgorithm::Aimd => self.additive_increase(policy),
+            LimitAlgorithm::Vegas => self.vegas(policy, latency_ms),
+        }
+    }
+
+    fn decrease(&mut self, policy: &ConcurrencyPolicy) {
+        let keep = 100 - u64::from(policy.decrease_percent.min(100));
+        // At most `limit`, so the narrowing cannot fail.
+        let limit = u32::try_from(u64::from(self.limit) * keep / 100).unwrap_or(self.limit);
+        self.limit = clamp_limit(limit, policy);
+        self.successes = 0;
+    }
+
+    // Grows the limit by one per `limit` successes, i.e. roughly one per
+    // round of in-flight requests.
+    fn additive_increase(&mut self, policy: &ConcurrencyPolicy) {
+        self.successes += 1;
+        if self.successes >= self.limit {
+            self.successes = 0;
+            self.limit = clamp_limit(self.limit.saturating_add(1), policy);
+        }
+    }
+
+    fn vegas(&mut self, policy: &ConcurrencyPolicy, latency_ms: u32) {
+        let latency_ms = latency_ms.max(1);
+        let min_latency_ms = match self.min_latency_ms {
+            Some(min) if min <= latency_ms => min,
+            _ => {
+                self.min_latency_ms = Some(latency_ms);
+                latency_ms
+            }
+        };
+
+        // Requests estimated to be queued upstream:
+        // limit * (1 - min_latency / latency).
+        let queue =
+            u64::from(self.limit) * u64::from(latency_ms - min_latency_ms) / u64::from(latency_ms);
+
+        if queue < u64::from(policy.vegas_alpha) {
+            self.limit = clamp_limit(self.limit.saturating_add(1), policy);
+        } else if queue > u64::from(policy.vegas_beta) {
+            self.limit = clamp_limit(self.limit.saturating_sub(1), policy);
+        }
+    }
+}
+
+fn clamp_limit(limit: u32, policy: &ConcurrencyPolicy) -> u32 {
+    let min = policy.min_limit.max(1);
+    limit.clamp(min, policy.max_limit.max(min))
+}
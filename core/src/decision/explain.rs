@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::AuthDecision,
+    error::ErrorCategory,
+    model::{Decision, HttpMethod, Outcome, Priority},
+    retry::HintMode,
+};
+
+/// The rules `decide` evaluated for one decision, in order, and what
+/// each of them found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    pub decision: Decision,
+}
+
+impl Explanation {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("explanation serializes to JSON")
+    }
+}
+
+/// One rule evaluated by `decide`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Step {
+    /// How the outcome was classified. `category` is set for
+    /// `HttpStatus` outcomes only.
+    Classification {
+        outcome: Outcome,
+        category: Option<ErrorCategory>,
+    },
+    /// Whether attempts are left.
+    AttemptLimit {
+        attempt: u8,
+        max_attempts: u8,
+        exhausted: bool,
+    },
+    /// Whether the method may be retried.
+    Idempotency {
+        method: HttpMethod,
+        idempotent: bool,
+        has_idempotency_key: bool,
+        allow_non_idempotent_retry: bool,
+        retryable: bool,
+    },
+    /// Auth coordination inputs for an auth error, with the state as it
+    /// was before this decision.
+    Auth {
+        refresh_attempted: bool,
+        auth_decision: Option<AuthDecision>,
+        refresh_result: Option<bool>,
+    },
+    /// How a server `retry_after_ms` hint became the retry delay.
+    RetryAfterHint {
+        hint_ms: u32,
+        clamped: bool,
+        backoff_ms: u32,
+        mode: HintMode,
+        after_ms: u32,
+    },
+    /// Whether a retry was shed because the client is overloaded.
+    LoadShedding {
+        priority: Priority,
+        overloaded: bool,
+        shed: bool,
+    },
+}
+
+/// Collects steps when an explanation was asked for; free otherwise.
+pub(crate) struct Trace(Option<Vec<Step>>);
+
+impl Trace {
+    pub(crate) fn enabled(enabled: bool) -> Self {
+        Trace(enabled.then(Vec::new))
+    }
+
+    pub(crate) fn push(&mut self, step: impl FnOnce() -> Step) {
+        if let Some(steps) = &mut self.0 {
+            steps.push(step());
+        }
+    }
+
+    pub(crate) fn finish(self, decision: &Decision) -> Option<Explanation> {
+        self.0.map(|steps| Explanation {
+            steps,
+            decision: decision.clone(),
+        })
+    }
+}
@@ -1,8 +1,15 @@
+mod explain;
+
+pub use explain::{Explanation, Step};
+
+pub(crate) use explain::Trace;
+
 use crate::{
     auth::{AuthDecision, AuthState},
     error::{classify_http_status, ErrorCategory},
-    model::{Decision, FailReason, Outcome, RequestContext, RetryReason},
-    retry::{can_retry, clamp_retry_after, retry_delay_ms},
+    model::{Decision, FailReason, Outcome, Priority, RequestContext, RetryReason},
+    policy::Policy,
+    retry::{can_retry, clamp_retry_after, combine_retry_after, is_idempotent, retry_delay_ms},
 };
 
 pub fn decide(
@@ -12,12 +19,158 @@ pub fn decide(
     auth_state: &mut AuthState,
     refresh_result: Option<bool>,
 ) -> Decision {
+    decide_with_policy(
+        ctx,
+        outcome,
+        auth_decision,
+        auth_state,
+        refresh_result,
+        &Policy::default(),
+    )
+}
+
+pub fn decide_with_policy(
+    ctx: &RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    auth_state: &mut AuthState,
+    refresh_result: Option<bool>,
+    policy: &Policy,
+) -> Decision {
+    decide_traced(
+        ctx,
+        outcome,
+        auth_decision,
+        auth_state,
+        refresh_result,
+        policy,
+        &mut Trace::enabled(false),
+    )
+}
+
+/// Like [`decide_with_policy`], also returning the rules evaluated.
+pub fn decide_explained(
+    ctx: &RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    auth_state: &mut AuthState,
+    refresh_result: Option<bool>,
+    policy: &Policy,
+) -> (Decision, Explanation) {
+    let mut trace = Trace::enabled(true);
+    let decision = decide_traced(
+        ctx,
+        outcome,
+        auth_decision,
+        auth_state,
+        refresh_result,
+        policy,
+        &mut trace,
+    );
+    let explanation = trace.finish(&decision).expect("trace is enabled");
+    (decision, explanation)
+}
+
+/// [`can_retry`], recording the attempt limit and idempotency checks.
+fn can_retry_traced(ctx: &RequestContext, trace: &mut Trace) -> bool {
+    let allowed = can_retry(ctx);
+    let exhausted = ctx.attempt >= ctx.max_attempts;
+
+    trace.push(|| Step::AttemptLimit {
+        attempt: ctx.attempt,
+        max_attempts: ctx.max_attempts,
+        exhausted,
+    });
+    if !exhausted {
+        trace.push(|| Step::Idempotency {
+            method: ctx.method.clone(),
+            idempotent: is_idempotent(&ctx.method),
+            has_idempotency_key: ctx.idempotency_key.is_some(),
+            allow_non_idempotent_retry: ctx.allow_non_idempotent_retry,
+            retryable: allowed,
+        });
+    }
+
+    allowed
+}
+
+pub(crate) fn decide_traced(
+    ctx: &RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    auth_state: &mut AuthState,
+    refresh_result: Option<bool>,
+    policy: &Policy,
+    trace: &mut Trace,
+) -> Decision {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!(
+        "decide",
+        key = ctx.idempotency_key.as_deref(),
+        partition = ctx.partition.as_deref(),
+        method = ?ctx.method,
+        attempt = ctx.attempt,
+        max_attempts = ctx.max_attempts,
+        outcome = ?outcome,
+    )
+    .entered();
+
+    let decision = evaluate(
+        ctx,
+        outcome,
+        auth_decision,
+        auth_state,
+        refresh_result,
+        policy,
+        trace,
+    );
+
+    trace_event!(debug, decision = ?decision, "decided");
+    decision
+}
+
+fn evaluate(
+    ctx: &RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    auth_state: &mut AuthState,
+    refresh_result: Option<bool>,
+    policy: &Policy,
+    trace: &mut Trace,
+) -> Decision {
+    trace.push(|| Step::Classification {
+        outcome: outcome.clone(),
+        category: match outcome {
+            Outcome::HttpStatus(status) => Some(classify_http_status(status)),
+            _ => None,
+        },
+    });
+
     match outcome {
         Outcome::RateLimited { retry_after_ms } => {
-            if can_retry(ctx) {
+            if can_retry_traced(ctx, trace) {
                 let base = retry_delay_ms(ctx, RetryReason::RateLimited);
                 let after_ms = match retry_after_ms {
-                    Some(ms) => clamp_retry_after(ms),
+                    Some(ms) => {
+                        if clamp_retry_after(ms) != ms {
+                            trace_event!(debug, hint_ms = ms, "Retry-After hint clamped");
+                        }
+                        let after_ms = combine_retry_after(
+                            ctx,
+                            ms,
+                            base,
+                            policy.retry.hint_mode,
+                            policy.retry.jitter_seed.unwrap_or(0),
+                        );
+                        trace.push(|| Step::RetryAfterHint {
+                            hint_ms: ms,
+                            clamped: clamp_retry_after(ms) != ms,
+                            backoff_ms: base,
+                            mode: policy.retry.hint_mode,
+                            after_ms,
+                        });
+                        after_ms
+                    }
                     None => base,
                 };
 
@@ -39,7 +192,7 @@ pub fn decide(
         },
 
         Outcome::NetworkError => {
-            if can_retry(ctx) {
+            if can_retry_traced(ctx, trace) {
                 Decision::Retry {
                     after_ms: retry_delay_ms(ctx, RetryReason::NetworkError),
                     reason: RetryReason::NetworkError,
@@ -53,7 +206,7 @@ pub fn decide(
         }
 
         Outcome::TimeoutError => {
-            if can_retry(ctx) {
+            if can_retry_traced(ctx, trace) {
                 Decision::Retry {
                     after_ms: retry_delay_ms(ctx, RetryReason::Timeout),
                     reason: RetryReason::Timeout,
@@ -72,7 +225,14 @@ pub fn decide(
         // from host environments when possible.
         Outcome::HttpStatus(status) => match classify_http_status(status) {
             ErrorCategory::AuthError => {
+                trace.push(|| Step::Auth {
+                    refresh_attempted: auth_state.refresh_attempted,
+                    auth_decision,
+                    refresh_result,
+                });
+
                 if auth_state.refresh_attempted {
+                    trace_event!(debug, "auth already refreshed for this request");
                     return Decision::Fail {
                         reason: FailReason::AuthFailed,
                         retryable: false,
@@ -83,11 +243,12 @@ pub fn decide(
                     Some(AuthDecision::RefreshAndRetry) => {
                         auth_state.refresh_attempted = true;
                         auth_state.refresh_in_progress = true;
+                        trace_event!(debug, refresh_result, "auth refresh");
 
                         match refresh_result {
                             Some(true) => {
                                 auth_state.refresh_in_progress = false;
-                                if can_retry(ctx) {
+                                if can_retry_traced(ctx, trace) {
                                     Decision::RefreshAndRetry {
                                         after_ms: retry_delay_ms(ctx, RetryReason::AuthExpired),
                                     }
@@ -116,7 +277,7 @@ pub fn decide(
             }
 
             ErrorCategory::RateLimitError => {
-                if can_retry(ctx) {
+                if can_retry_traced(ctx, trace) {
                     Decision::Retry {
                         after_ms: retry_delay_ms(ctx, RetryReason::RateLimited),
                         reason: RetryReason::RateLimited,
@@ -136,3 +297,20 @@ pub fn decide(
         },
     }
 }
+
+/// Turns a `Retry` into `Fail(Shed)` when the client is overloaded for
+/// the request's priority. `Critical` requests are never shed.
+///
+/// The failure is retryable: the request itself was fine, it was only
+/// dropped to protect higher-priority work.
+pub fn shed_retry(ctx: &RequestContext, decision: Decision, overloaded: bool) -> Decision {
+    match decision {
+        Decision::Retry { .. } if overloaded && ctx.priority < Priority::Critical => {
+            Decision::Fail {
+                reason: FailReason::Shed,
+                retryable: true,
+            }
+        }
+        other => other,
+    }
+}
@@ -1,38 +1,466 @@
+// Every entrypoint takes raw pointers from C and checks them for null
+// itself; marking them `unsafe fn` would not change anything for callers.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use std::{
+    cell::{Cell, RefCell},
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int, c_void},
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
 use crate::{
-    auth::{AuthDecision, AuthState},
-    decision::decide,
-    model::{Decision, HttpMethod, Outcome, RequestContext},
+    auth::AuthDecision,
+    client::{Client, ExportedState},
+    concurrency::{Admission, ConcurrencyPolicy, LimitAlgorithm},
+    json,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+    observer::{DecisionEvent, Observer},
+    retry::HintMode,
+    stats::TimeoutPolicy,
 };
 
-#[repr(C)]
+/// ABI version of this library. Bumped on any breaking change to the
+/// C interface.
+pub const TRANSPORT_CORE_ABI_VERSION: u32 = 2;
+
+/// Opaque client handle.
+///
+/// Holds authentication coordination state and the client's policy.
+/// Allocated by `tc_client_new`, released by `tc_client_free`.
+#[allow(non_camel_case_types)]
 pub struct transport_core_client {
-    auth_state: AuthState,
-    last_decision: Option<Decision>,
+    client: Client,
 }
 
 #[no_mangle]
 pub extern "C" fn tc_client_new() -> *mut transport_core_client {
-    Box::into_raw(Box::new(transport_core_client {
-        auth_state: AuthState::new(),
-        last_decision: None,
-    }))
+    guard(std::ptr::null_mut(), || {
+        Ok(Box::into_raw(Box::new(transport_core_client {
+            client: Client::new(),
+        })))
+    })
 }
 
+/// NULL is ignored.
 #[no_mangle]
 pub extern "C" fn tc_client_free(ptr: *mut transport_core_client) {
-    if !ptr.is_null() {
-        unsafe {
-            drop(Box::from_raw(ptr));
+    guard((), || {
+        if !ptr.is_null() {
+            unsafe {
+                drop(Box::from_raw(ptr));
+            }
+        }
+        Ok(())
+    })
+}
+
+/* ============================
+ * Error reporting
+ * ============================ */
+
+/// Status of the most recent call on the calling thread, from
+/// `tc_last_status`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum tc_status_t {
+    TC_STATUS_OK = 0,
+    /// A required pointer was NULL or an integer was out of range.
+    TC_STATUS_INVALID_ARGUMENT = 1,
+    /// An enum argument or field held a value the enum does not define.
+    TC_STATUS_INVALID_ENUM = 2,
+    /// A string argument was not valid UTF-8.
+    TC_STATUS_INVALID_UTF8 = 3,
+    /// The library panicked. The client's state may be inconsistent and
+    /// it should be freed.
+    TC_STATUS_PANIC = 4,
+}
+
+struct FfiError {
+    status: tc_status_t,
+    message: String,
+}
+
+impl FfiError {
+    fn null(name: &str) -> Self {
+        FfiError {
+            status: tc_status_t::TC_STATUS_INVALID_ARGUMENT,
+            message: format!("{name} is NULL"),
         }
     }
 }
 
+thread_local! {
+    static LAST_STATUS: Cell<tc_status_t> = const { Cell::new(tc_status_t::TC_STATUS_OK) };
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_status(status: tc_status_t, message: Option<String>) {
+    LAST_STATUS.with(|s| s.set(status));
+    LAST_ERROR.with(|e| {
+        *e.borrow_mut() =
+            message.map(|m| CString::new(m.replace('\0', " ")).expect("NUL bytes were replaced"))
+    });
+}
+
+/// Runs an entrypoint body, recording its status for `tc_last_status`.
+///
+/// Errors and panics return `fallback`; panics never unwind into C.
+fn guard<T>(fallback: T, body: impl FnOnce() -> Result<T, FfiError>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => {
+            set_status(tc_status_t::TC_STATUS_OK, None);
+            value
+        }
+        Ok(Err(err)) => {
+            set_status(err.status, Some(err.message));
+            fallback
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|m| m.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_status(tc_status_t::TC_STATUS_PANIC, Some(message));
+            fallback
+        }
+    }
+}
+
+/// Status of the most recent call on the calling thread. Every function
+/// except the version queries and the two error getters records one.
+#[no_mangle]
+pub extern "C" fn tc_last_status() -> tc_status_t {
+    LAST_STATUS.with(Cell::get)
+}
+
+/// Message describing the most recent failed call on the calling thread,
+/// or NULL if it succeeded. Owned by the library and valid until the
+/// next call on the same thread; MUST NOT be freed.
+#[no_mangle]
+pub extern "C" fn tc_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |m| m.as_ptr()))
+}
+
+fn client_ref<'a>(client: *const transport_core_client) -> Result<&'a Client, FfiError> {
+    if client.is_null() {
+        return Err(FfiError::null("client"));
+    }
+    Ok(unsafe { &(*client).client })
+}
+
+fn client_mut<'a>(client: *mut transport_core_client) -> Result<&'a mut Client, FfiError> {
+    if client.is_null() {
+        return Err(FfiError::null("client"));
+    }
+    Ok(unsafe { &mut (*client).client })
+}
+
+fn arg_ref<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::null(name));
+    }
+    Ok(unsafe { &*ptr })
+}
+
+/// Reads a nullable C string.
+fn c_string(ptr: *const c_char, name: &str) -> Result<Option<String>, FfiError> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+
+    match unsafe { CStr::from_ptr(ptr) }.to_str() {
+        Ok(s) => Ok(Some(s.to_string())),
+        Err(_) => Err(FfiError {
+            status: tc_status_t::TC_STATUS_INVALID_UTF8,
+            message: format!("{name} is not valid UTF-8"),
+        }),
+    }
+}
+
+/// Enums cross the boundary as plain `int`s: a value C passes that the
+/// Rust enum does not define would be undefined behaviour, so each is
+/// checked with `from_raw` before use.
+macro_rules! from_raw {
+    ($t:ident { $($variant:ident),+ $(,)? }) => {
+        impl $t {
+            fn from_raw(raw: c_int) -> Result<Self, FfiError> {
+                $(
+                    if raw == $t::$variant as c_int {
+                        return Ok($t::$variant);
+                    }
+                )+
+                Err(FfiError {
+                    status: tc_status_t::TC_STATUS_INVALID_ENUM,
+                    message: format!("{raw} is not a valid {}", stringify!($t)),
+                })
+            }
+        }
+    };
+}
+
+/* ============================
+ * Version / capabilities
+ * ============================ */
+
+/// Capabilities reported by `tc_feature_supported`.
+const FEATURES: &[&str] = &[
+    "hint_modes",
+    "hedging",
+    "adaptive_timeout",
+    "concurrency_limit",
+    "load_shedding",
+    "json",
+    "state_reset",
+    "state_export",
+    "explain",
+    "observer",
+    "metrics",
+    "recording",
+];
+
+/// ABI version the library was built with. Hosts MUST refuse to use the
+/// library when it differs from the `TRANSPORT_CORE_ABI_VERSION` they
+/// were built against.
+#[no_mangle]
+pub extern "C" fn tc_abi_version() -> u32 {
+    TRANSPORT_CORE_ABI_VERSION
+}
+
+/// Semver version of the library, e.g. "0.1.0". The string is static and
+/// MUST NOT be freed.
+#[no_mangle]
+pub extern "C" fn tc_library_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Whether the library supports the named capability, e.g. "hedging".
+/// Unknown names return false, as do NULL and non-UTF-8 input (which
+/// also set `tc_last_status`).
+#[no_mangle]
+pub extern "C" fn tc_feature_supported(name: *const c_char) -> bool {
+    guard(false, || {
+        let name = c_string(arg_ref(name, "name")?, "name")?;
+        Ok(name.is_some_and(|name| FEATURES.contains(&name.as_str())))
+    })
+}
+
+/* ============================
+ * Policy configuration
+ * ============================ */
+
+/// How a RATE_LIMITED `retry_after_ms` hint is combined with the
+/// computed backoff.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum tc_hint_mode_t {
+    TC_HINT_ONLY = 0,
+    TC_HINT_MAX_OF_BACKOFF = 1,
+    TC_HINT_PLUS_JITTER = 2,
+}
+
+from_raw!(tc_hint_mode_t {
+    TC_HINT_ONLY,
+    TC_HINT_MAX_OF_BACKOFF,
+    TC_HINT_PLUS_JITTER,
+});
+
+/// `mode`: a `tc_hint_mode_t` value.
+///
+/// `max_jitter_ms` is only used by `TC_HINT_PLUS_JITTER`. A non-zero
+/// `jitter_seed` fixes the jitter, for reproducible tests; 0 keeps the
+/// seed each client draws for itself.
+#[no_mangle]
+pub extern "C" fn tc_client_set_hint_mode(
+    client: *mut transport_core_client,
+    mode: c_int,
+    max_jitter_ms: u32,
+    jitter_seed: u64,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+        let mode = tc_hint_mode_t::from_raw(mode)?;
+
+        let retry = &mut client.policy_mut().retry;
+
+        retry.hint_mode = match mode {
+            tc_hint_mode_t::TC_HINT_ONLY => HintMode::HintOnly,
+            tc_hint_mode_t::TC_HINT_MAX_OF_BACKOFF => HintMode::MaxOfHintAndBackoff,
+            tc_hint_mode_t::TC_HINT_PLUS_JITTER => HintMode::HintPlusJitter { max_jitter_ms },
+        };
+        retry.jitter_seed = (jitter_seed != 0).then_some(jitter_seed);
+        Ok(())
+    })
+}
+
+/// Hedging is disabled until `max_hedges > 0`. `delay_ms` is how long
+/// the host waits without a response before sending each hedge.
+#[no_mangle]
+pub extern "C" fn tc_client_set_hedge_policy(
+    client: *mut transport_core_client,
+    max_hedges: u8,
+    delay_ms: u32,
+) {
+    guard((), || {
+        let hedge = &mut client_mut(client)?.policy_mut().hedge;
+        hedge.max_hedges = max_hedges;
+        hedge.delay_ms = delay_ms;
+        Ok(())
+    })
+}
+
+/// Adapts hedging to observed traffic; 0 disables either part.
+///
+/// With `delay_percentile` (1..=100), hedges are planned after that
+/// latency percentile of the request's partition once it has
+/// `min_samples` samples, and after `delay_ms` until then. With
+/// `budget_percent`, hedges planned stay within that percentage of the
+/// requests reported through `tc_report_hedge_result`.
+///
+/// Fails with TC_STATUS_INVALID_ARGUMENT if `delay_percentile` is over
+/// 100.
+#[no_mangle]
+pub extern "C" fn tc_client_set_hedge_adaptation(
+    client: *mut transport_core_client,
+    delay_percentile: u8,
+    min_samples: u32,
+    budget_percent: u32,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+
+        if delay_percentile > 100 {
+            return Err(FfiError {
+                status: tc_status_t::TC_STATUS_INVALID_ARGUMENT,
+                message: format!("delay_percentile {delay_percentile} is not in 0..=100"),
+            });
+        }
+
+        let hedge = &mut client.policy_mut().hedge;
+        hedge.delay_percentile = delay_percentile;
+        hedge.min_samples = min_samples as usize;
+        hedge.budget_percent = budget_percent;
+        Ok(())
+    })
+}
+
+/// Adaptive timeout policy.
+#[repr(C)]
+pub struct tc_timeout_policy_t {
+    /// Latency percentile, 1..=100.
+    pub percentile: u8,
+    /// Multiplier applied to the percentile, in percent.
+    pub factor_percent: u32,
+    pub min_ms: u32,
+    pub max_ms: u32,
+    /// Used until `min_samples` attempts were reported.
+    pub default_ms: u32,
+    pub min_samples: u32,
+    /// Most recent samples kept per partition.
+    pub window: u32,
+}
+
+#[no_mangle]
+pub extern "C" fn tc_client_set_timeout_policy(
+    client: *mut transport_core_client,
+    policy: *const tc_timeout_policy_t,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+        let policy = arg_ref(policy, "policy")?;
+
+        if !(1..=100).contains(&policy.percentile) {
+            return Err(FfiError {
+                status: tc_status_t::TC_STATUS_INVALID_ARGUMENT,
+                message: format!("percentile {} is not in 1..=100", policy.percentile),
+            });
+        }
+
+        client.policy_mut().timeout = TimeoutPolicy {
+            percentile: policy.percentile,
+            factor_percent: policy.factor_percent,
+            min_ms: policy.min_ms,
+            max_ms: policy.max_ms,
+            default_ms: policy.default_ms,
+            min_samples: policy.min_samples as usize,
+            window: policy.window as usize,
+        };
+        Ok(())
+    })
+}
+
+/// Concurrency limit algorithm.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum tc_limit_algorithm_t {
+    TC_LIMIT_AIMD = 0,
+    TC_LIMIT_VEGAS = 1,
+}
+
+from_raw!(tc_limit_algorithm_t {
+    TC_LIMIT_AIMD,
+    TC_LIMIT_VEGAS,
+});
+
+/// Adaptive concurrency policy.
+#[repr(C)]
+pub struct tc_concurrency_policy_t {
+    /// A `tc_limit_algorithm_t` value.
+    pub algorithm: c_int,
+    pub initial_limit: u32,
+    pub min_limit: u32,
+    pub max_limit: u32,
+    /// Share of the limit removed on overload, in percent.
+    pub decrease_percent: u8,
+    /// 0 = reject as soon as the limit is reached.
+    pub max_queue: u32,
+    /// Share of the limit, in percent, `TC_PRIORITY_BACKGROUND` may use.
+    pub background_share_percent: u8,
+    pub vegas_alpha: u32,
+    pub vegas_beta: u32,
+}
+
+/// Replaces the concurrency policy and restarts limit adaptation.
+#[no_mangle]
+pub extern "C" fn tc_client_set_concurrency_policy(
+    client: *mut transport_core_client,
+    policy: *const tc_concurrency_policy_t,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+        let policy = arg_ref(policy, "policy")?;
+
+        let algorithm = match tc_limit_algorithm_t::from_raw(policy.algorithm)? {
+            tc_limit_algorithm_t::TC_LIMIT_AIMD => LimitAlgorithm::Aimd,
+            tc_limit_algorithm_t::TC_LIMIT_VEGAS => LimitAlgorithm::Vegas,
+        };
+
+        client.set_concurrency_policy(ConcurrencyPolicy {
+            algorithm,
+            initial_limit: policy.initial_limit,
+            min_limit: policy.min_limit,
+            max_limit: policy.max_limit,
+            decrease_percent: policy.decrease_percent,
+            max_queue: policy.max_queue,
+            background_share_percent: policy.background_share_percent,
+            vegas_alpha: policy.vegas_alpha,
+            vegas_beta: policy.vegas_beta,
+        });
+        Ok(())
+    })
+}
+
 /* ============================
  * tc_decide (ABI entrypoint)
  * ============================ */
 
+/// Coarse decision returned by `tc_decide`.
 #[repr(C)]
 #[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum tc_decision_t {
     TC_DECISION_PROCEED = 0,
     TC_DECISION_RETRY = 1,
@@ -40,6 +468,7 @@ pub enum tc_decision_t {
     TC_DECISION_FAIL = 3,
 }
 
+/// Auth decision (from host).
 #[repr(C)]
 #[allow(non_camel_case_types)]
 pub enum tc_auth_decision_t {
@@ -47,6 +476,11 @@ pub enum tc_auth_decision_t {
     TC_AUTH_FAIL = 1,
 }
 
+from_raw!(tc_auth_decision_t {
+    TC_AUTH_REFRESH_AND_RETRY,
+    TC_AUTH_FAIL,
+});
+
 #[repr(C)]
 #[allow(non_camel_case_types)]
 pub enum tc_http_method_t {
@@ -58,6 +492,16 @@ pub enum tc_http_method_t {
     TC_HTTP_OPTIONS,
 }
 
+from_raw!(tc_http_method_t {
+    TC_HTTP_GET,
+    TC_HTTP_POST,
+    TC_HTTP_PUT,
+    TC_HTTP_DELETE,
+    TC_HTTP_HEAD,
+    TC_HTTP_OPTIONS,
+});
+
+/// Semantic outcome.
 #[repr(C)]
 #[allow(non_camel_case_types)]
 pub enum tc_outcome_kind_t {
@@ -65,47 +509,95 @@ pub enum tc_outcome_kind_t {
     TC_OUTCOME_TIMEOUT_ERROR,
     TC_OUTCOME_HTTP_STATUS,
 
-    // NEW semantic outcomes
+    // semantic-first outcomes
     TC_OUTCOME_RATE_LIMITED,
     TC_OUTCOME_BLOCKED,
     TC_OUTCOME_CAPTCHA,
 }
 
+from_raw!(tc_outcome_kind_t {
+    TC_OUTCOME_NETWORK_ERROR,
+    TC_OUTCOME_TIMEOUT_ERROR,
+    TC_OUTCOME_HTTP_STATUS,
+    TC_OUTCOME_RATE_LIMITED,
+    TC_OUTCOME_BLOCKED,
+    TC_OUTCOME_CAPTCHA,
+});
+
+/// Request priority. Lower priorities are shed first.
+///
+/// NORMAL is 0 so that a zeroed context has normal priority; the values
+/// are not in shedding order.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum tc_priority_t {
+    TC_PRIORITY_NORMAL = 0,
+    TC_PRIORITY_CRITICAL = 1,
+    TC_PRIORITY_BACKGROUND = 2,
+}
+
+from_raw!(tc_priority_t {
+    TC_PRIORITY_NORMAL,
+    TC_PRIORITY_CRITICAL,
+    TC_PRIORITY_BACKGROUND,
+});
+
+/// Reason for a `TC_DECISION_RETRY`, from `tc_last_retry_reason`.
+/// Values match `model::RetryReason`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum tc_retry_reason_t {
+    /// The last decision was not a retry.
+    TC_RETRY_REASON_NONE = 0,
+    TC_RETRY_REASON_NETWORK = 1,
+    TC_RETRY_REASON_TIMEOUT = 2,
+    TC_RETRY_REASON_RATE_LIMITED = 3,
+    TC_RETRY_REASON_AUTH_EXPIRED = 4,
+}
+
+/// Reason for a `TC_DECISION_FAIL`, from `tc_last_fail_reason`.
+/// Values match `model::FailReason`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum tc_fail_reason_t {
+    /// The last decision was not a failure.
+    TC_FAIL_REASON_NONE = 0,
+    TC_FAIL_REASON_MAX_ATTEMPTS = 1,
+    TC_FAIL_REASON_AUTH_FAILED = 2,
+    TC_FAIL_REASON_HARD_BLOCKED = 3,
+    TC_FAIL_REASON_SHED = 4,
+    TC_FAIL_REASON_UNKNOWN = 255,
+}
+
+/// Request context.
 #[repr(C)]
 pub struct tc_request_context_t {
-    pub method: tc_http_method_t,
+    /// A `tc_http_method_t` value.
+    pub method: c_int,
     pub attempt: u8,
     pub max_attempts: u8,
     pub allow_non_idempotent_retry: bool,
-    pub idempotency_key: *const std::os::raw::c_char,
+    /// Nullable.
+    pub idempotency_key: *const c_char,
+    /// Nullable. Added in ABI v2.
+    pub partition: *const c_char,
+    /// A `tc_priority_t` value. Added in ABI v2.
+    pub priority: c_int,
 }
 
+/// Outcome.
 #[repr(C)]
 pub struct tc_outcome_t {
-    pub kind: tc_outcome_kind_t,
+    /// A `tc_outcome_kind_t` value.
+    pub kind: c_int,
+    /// Only valid for `TC_OUTCOME_HTTP_STATUS`.
     pub http_status: u16,
+    /// Only valid for `TC_OUTCOME_RATE_LIMITED`.
     pub retry_after_ms: u32,
 }
 
-#[no_mangle]
-pub extern "C" fn tc_decide(
-    client: *mut transport_core_client,
-    ctx: *const tc_request_context_t,
-    outcome: *const tc_outcome_t,
-    auth_decision: tc_auth_decision_t,
-    refresh_result: i8,
-) -> tc_decision_t {
-    // Basic safety
-    if client.is_null() || ctx.is_null() || outcome.is_null() {
-        return tc_decision_t::TC_DECISION_FAIL;
-    }
-
-    let client = unsafe { &mut *client };
-    let ctx = unsafe { &*ctx };
-    let outcome = unsafe { &*outcome };
-
-    // Map HTTP method
-    let method = match ctx.method {
+fn request_context(ctx: &tc_request_context_t) -> Result<RequestContext, FfiError> {
+    let method = match tc_http_method_t::from_raw(ctx.method)? {
         tc_http_method_t::TC_HTTP_GET => HttpMethod::GET,
         tc_http_method_t::TC_HTTP_POST => HttpMethod::POST,
         tc_http_method_t::TC_HTTP_PUT => HttpMethod::PUT,
@@ -114,15 +606,34 @@ pub extern "C" fn tc_decide(
         tc_http_method_t::TC_HTTP_OPTIONS => HttpMethod::OPTIONS,
     };
 
-    let req_ctx = RequestContext {
+    Ok(RequestContext {
         method,
         attempt: ctx.attempt,
         max_attempts: ctx.max_attempts,
-        idempotency_key: None,
+        idempotency_key: c_string(ctx.idempotency_key, "idempotency_key")?,
         allow_non_idempotent_retry: ctx.allow_non_idempotent_retry,
-    };
+        partition: c_string(ctx.partition, "partition")?,
+        priority: priority_from_c(ctx.priority)?,
+    })
+}
+
+fn priority_from_c(priority: c_int) -> Result<Priority, FfiError> {
+    Ok(match tc_priority_t::from_raw(priority)? {
+        tc_priority_t::TC_PRIORITY_NORMAL => Priority::Normal,
+        tc_priority_t::TC_PRIORITY_CRITICAL => Priority::Critical,
+        tc_priority_t::TC_PRIORITY_BACKGROUND => Priority::Background,
+    })
+}
 
-    let rust_outcome = match outcome.kind {
+/// Reads a nullable outcome; NULL means success.
+fn outcome_from_c(outcome: *const tc_outcome_t) -> Result<Option<Outcome>, FfiError> {
+    if outcome.is_null() {
+        return Ok(None);
+    }
+
+    let outcome = unsafe { &*outcome };
+
+    Ok(Some(match tc_outcome_kind_t::from_raw(outcome.kind)? {
         tc_outcome_kind_t::TC_OUTCOME_NETWORK_ERROR => Outcome::NetworkError,
         tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR => Outcome::TimeoutError,
         tc_outcome_kind_t::TC_OUTCOME_HTTP_STATUS => Outcome::HttpStatus(outcome.http_status),
@@ -139,93 +650,669 @@ pub extern "C" fn tc_decide(
         }
         tc_outcome_kind_t::TC_OUTCOME_BLOCKED => Outcome::Blocked,
         tc_outcome_kind_t::TC_OUTCOME_CAPTCHA => Outcome::Captcha,
-    };
+    }))
+}
 
-    let auth_decision = match auth_decision {
-        tc_auth_decision_t::TC_AUTH_REFRESH_AND_RETRY => Some(AuthDecision::RefreshAndRetry),
-        tc_auth_decision_t::TC_AUTH_FAIL => None,
-    };
+/// Evaluates retry eligibility and auth coordination, updating the
+/// client's auth state. Details are available through the
+/// `tc_last_*` getters.
+///
+/// `auth_decision`: a `tc_auth_decision_t` value.
+/// `refresh_result`: -1 = refresh not attempted, 0 = refresh failed,
+/// 1 = refresh succeeded.
+///
+/// On invalid arguments returns `TC_DECISION_FAIL` without touching the
+/// client; check `tc_last_status` to tell it from a policy failure.
+#[no_mangle]
+pub extern "C" fn tc_decide(
+    client: *mut transport_core_client,
+    ctx: *const tc_request_context_t,
+    outcome: *const tc_outcome_t,
+    auth_decision: c_int,
+    refresh_result: i8,
+) -> tc_decision_t {
+    guard(tc_decision_t::TC_DECISION_FAIL, || {
+        let client = client_mut(client)?;
+        let req_ctx = request_context(arg_ref(ctx, "ctx")?)?;
+        let rust_outcome =
+            outcome_from_c(arg_ref(outcome, "outcome")?)?.expect("outcome was checked for NULL");
 
-    let refresh_result = match refresh_result {
-        1 => Some(true),
-        0 => Some(false),
-        _ => None,
-    };
+        let auth_decision = match tc_auth_decision_t::from_raw(auth_decision)? {
+            tc_auth_decision_t::TC_AUTH_REFRESH_AND_RETRY => Some(AuthDecision::RefreshAndRetry),
+            tc_auth_decision_t::TC_AUTH_FAIL => None,
+        };
 
-    let decision = decide(
-        &req_ctx,
-        rust_outcome,
-        auth_decision,
-        &mut client.auth_state,
-        refresh_result,
-    );
+        let refresh_result = match refresh_result {
+            1 => Some(true),
+            0 => Some(false),
+            -1 => None,
+            other => {
+                return Err(FfiError {
+                    status: tc_status_t::TC_STATUS_INVALID_ARGUMENT,
+                    message: format!("refresh_result {other} is not -1, 0 or 1"),
+                })
+            }
+        };
 
-    client.last_decision = Some(decision.clone());
+        let decision = client.decide(&req_ctx, rust_outcome, auth_decision, refresh_result);
 
-    match decision {
-        Decision::Proceed => tc_decision_t::TC_DECISION_PROCEED,
+        Ok(match decision {
+            Decision::Proceed => tc_decision_t::TC_DECISION_PROCEED,
 
-        Decision::Retry { .. } => tc_decision_t::TC_DECISION_RETRY,
+            Decision::Retry { .. } => tc_decision_t::TC_DECISION_RETRY,
 
-        Decision::RefreshAndRetry { .. } => tc_decision_t::TC_DECISION_REFRESH_AND_RETRY,
+            Decision::RefreshAndRetry { .. } => tc_decision_t::TC_DECISION_REFRESH_AND_RETRY,
 
-        Decision::Fail { .. } => tc_decision_t::TC_DECISION_FAIL,
-    }
+            Decision::Fail { .. } => tc_decision_t::TC_DECISION_FAIL,
+        })
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn tc_last_retry_after_ms(client: *const transport_core_client) -> u32 {
-    if client.is_null() {
-        return 0;
-    }
-
-    let client = unsafe { &*client };
+    guard(0, || {
+        Ok(match client_ref(client)?.last_decision() {
+            Some(Decision::Retry { after_ms, .. }) => *after_ms,
+            Some(Decision::RefreshAndRetry { after_ms }) => *after_ms,
+            _ => 0,
+        })
+    })
+}
 
-    match &client.last_decision {
-        Some(Decision::Retry { after_ms, .. }) => *after_ms,
-        Some(Decision::RefreshAndRetry { after_ms }) => *after_ms,
-        _ => 0,
-    }
+/// Recommended per-attempt timeout for a RETRY / REFRESH_AND_RETRY,
+/// else 0.
+#[no_mangle]
+pub extern "C" fn tc_last_timeout_ms(client: *const transport_core_client) -> u32 {
+    guard(0, || Ok(client_ref(client)?.last_timeout_ms().unwrap_or(0)))
 }
 
+/// A `tc_retry_reason_t` value.
 #[no_mangle]
 pub extern "C" fn tc_last_retry_reason(client: *const transport_core_client) -> u8 {
-    if client.is_null() {
-        return 0;
-    }
+    guard(0, || {
+        Ok(match client_ref(client)?.last_decision() {
+            Some(Decision::Retry { reason, .. }) => *reason as u8,
+            _ => 0,
+        })
+    })
+}
 
-    let client = unsafe { &*client };
+/// A `tc_fail_reason_t` value.
+#[no_mangle]
+pub extern "C" fn tc_last_fail_reason(client: *const transport_core_client) -> u8 {
+    guard(0, || {
+        Ok(match client_ref(client)?.last_decision() {
+            Some(Decision::Fail { reason, .. }) => *reason as u8,
+            _ => 0,
+        })
+    })
+}
 
-    match &client.last_decision {
-        Some(Decision::Retry { reason, .. }) => *reason as u8,
-        _ => 0,
-    }
+#[no_mangle]
+pub extern "C" fn tc_last_fail_retryable(client: *const transport_core_client) -> bool {
+    guard(false, || {
+        Ok(match client_ref(client)?.last_decision() {
+            Some(Decision::Fail { retryable, .. }) => *retryable,
+            _ => false,
+        })
+    })
 }
 
+/* ============================
+ * JSON entrypoint
+ * ============================ */
+
+/// JSON-in/JSON-out variant of `tc_decide` using the client's state.
+///
+/// input:  `{"ctx": {...}, "outcome": ..., "auth_decision": ...,
+///           "refresh_result": ...}`
+/// output: `{"decision": ..., "auth_state": {...}}` or `{"error": "..."}`
+///
+/// Returns 0 when `*output` holds a decision. Returns -1 on NULL
+/// arguments or non-UTF-8 input (nothing written; see `tc_last_status`),
+/// or 1 on a malformed request (`*output` holds the error).
+/// `*output` MUST be freed with `tc_string_free`.
 #[no_mangle]
-pub extern "C" fn tc_last_fail_reason(client: *const transport_core_client) -> u8 {
-    if client.is_null() {
-        return 0;
-    }
+pub extern "C" fn tc_decide_json(
+    client: *mut transport_core_client,
+    input: *const c_char,
+    output: *mut *mut c_char,
+) -> i32 {
+    guard(-1, || {
+        let client = client_mut(client)?;
+        let input =
+            c_string(arg_ref(input, "input")?, "input")?.expect("input was checked for NULL");
+        if output.is_null() {
+            return Err(FfiError::null("output"));
+        }
 
-    let client = unsafe { &*client };
+        let response = json::decide_with_client(client, &input);
+        let status = if response.is_error() { 1 } else { 0 };
 
-    match &client.last_decision {
-        Some(Decision::Fail { reason, .. }) => *reason as u8,
-        _ => 0,
-    }
+        unsafe { *output = into_c_string(response.to_json()) };
+
+        Ok(status)
+    })
 }
 
+/// Frees a string returned by this library. NULL is ignored.
 #[no_mangle]
-pub extern "C" fn tc_last_fail_retryable(client: *const transport_core_client) -> bool {
-    if client.is_null() {
-        return false;
+pub extern "C" fn tc_string_free(s: *mut c_char) {
+    guard((), || {
+        if !s.is_null() {
+            unsafe {
+                drop(CString::from_raw(s));
+            }
+        }
+        Ok(())
+    })
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // JSON escapes control characters, so no interior NUL can occur.
+    CString::new(s)
+        .expect("JSON output contains no NUL bytes")
+        .into_raw()
+}
+
+/* ============================
+ * Latency statistics
+ * ============================ */
+
+/// Reports one finished attempt.
+///
+/// `partition`: nullable; NULL uses the client-wide default partition.
+/// `outcome`: nullable; NULL means the attempt succeeded.
+#[no_mangle]
+pub extern "C" fn tc_record_attempt(
+    client: *mut transport_core_client,
+    partition: *const c_char,
+    latency_ms: u32,
+    outcome: *const tc_outcome_t,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+        let partition = c_string(partition, "partition")?;
+        let outcome = outcome_from_c(outcome)?;
+
+        client.record_attempt(partition.as_deref(), latency_ms, outcome.as_ref());
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn tc_recommended_timeout_ms(
+    client: *const transport_core_client,
+    partition: *const c_char,
+) -> u32 {
+    guard(0, || {
+        let client = client_ref(client)?;
+        let partition = c_string(partition, "partition")?;
+
+        Ok(client.recommended_timeout_ms(partition.as_deref()))
+    })
+}
+
+/* ============================
+ * Concurrency limiting
+ * ============================ */
+
+/// Admission (from `tc_acquire`).
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum tc_admission_t {
+    TC_ADMISSION_ACQUIRED = 0,
+    TC_ADMISSION_QUEUED = 1,
+    TC_ADMISSION_REJECTED = 2,
+}
+
+/// Call before sending a request.
+///
+/// - ACQUIRED: send it, then call `tc_release` when it finishes.
+/// - QUEUED: wait, then call again with `queued = true`
+///   (or `tc_cancel_queued` to give up).
+/// - REJECTED: do not send it.
+///
+/// Queued requests are served before new ones. BACKGROUND requests are
+/// rejected beyond their share of the limit; CRITICAL requests may queue
+/// up to one limit's worth past `max_queue`.
+///
+/// `priority`: a `tc_priority_t` value.
+#[no_mangle]
+pub extern "C" fn tc_acquire(
+    client: *mut transport_core_client,
+    queued: bool,
+    priority: c_int,
+) -> tc_admission_t {
+    guard(tc_admission_t::TC_ADMISSION_REJECTED, || {
+        let client = client_mut(client)?;
+        let priority = priority_from_c(priority)?;
+
+        Ok(match client.acquire(queued, priority) {
+            Admission::Acquired => tc_admission_t::TC_ADMISSION_ACQUIRED,
+            Admission::Queued => tc_admission_t::TC_ADMISSION_QUEUED,
+            Admission::Rejected => tc_admission_t::TC_ADMISSION_REJECTED,
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn tc_cancel_queued(client: *mut transport_core_client) {
+    guard((), || {
+        client_mut(client)?.cancel_queued();
+        Ok(())
+    })
+}
+
+/// `outcome`: nullable; NULL means the request succeeded.
+#[no_mangle]
+pub extern "C" fn tc_release(
+    client: *mut transport_core_client,
+    latency_ms: u32,
+    outcome: *const tc_outcome_t,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+        let outcome = outcome_from_c(outcome)?;
+
+        client.release(latency_ms, outcome.as_ref());
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn tc_concurrency_limit(client: *const transport_core_client) -> u32 {
+    guard(0, || Ok(client_ref(client)?.concurrency().limit()))
+}
+
+/* ============================
+ * Hedging
+ * ============================ */
+
+/// `hedges_sent`: hedges already sent for this request (0 before the
+/// first).
+///
+/// Returns true and writes `*after_ms` if the host should send another
+/// duplicate attempt once `after_ms` elapse without a response. Only
+/// idempotent methods are hedged. The planned hedge counts against the
+/// budget until the request is reported through `tc_report_hedge_result`.
+#[no_mangle]
+pub extern "C" fn tc_plan_hedge(
+    client: *mut transport_core_client,
+    ctx: *const tc_request_context_t,
+    hedges_sent: u8,
+    after_ms: *mut u32,
+) -> bool {
+    guard(false, || {
+        let client = client_mut(client)?;
+        let ctx = request_context(arg_ref(ctx, "ctx")?)?;
+        if after_ms.is_null() {
+            return Err(FfiError::null("after_ms"));
+        }
+
+        Ok(match client.plan_hedge(&ctx, hedges_sent) {
+            Some(ms) => {
+                unsafe { *after_ms = ms };
+                true
+            }
+            None => false,
+        })
+    })
+}
+
+/// Reports a finished hedged request and the number of hedges it sent,
+/// settling the hedges planned for it.
+#[no_mangle]
+pub extern "C" fn tc_report_hedge_result(client: *mut transport_core_client, hedges_sent: u8) {
+    guard((), || {
+        client_mut(client)?.report_hedge_result(hedges_sent);
+        Ok(())
+    })
+}
+
+/* ============================
+ * State reset / introspection
+ * ============================ */
+
+/// Clears the auth coordination state so a later 401 may refresh again.
+#[no_mangle]
+pub extern "C" fn tc_client_reset_auth(client: *mut transport_core_client) {
+    guard((), || {
+        client_mut(client)?.reset_auth();
+        Ok(())
+    })
+}
+
+/// Forgets all learned state but keeps the policy. In-flight and queued
+/// counts are kept so outstanding slots can still be released.
+#[no_mangle]
+pub extern "C" fn tc_client_reset(client: *mut transport_core_client) {
+    guard((), || {
+        client_mut(client)?.reset();
+        Ok(())
+    })
+}
+
+/// Snapshot of a client's state (from `tc_client_state`).
+#[repr(C)]
+pub struct tc_client_state_t {
+    pub refresh_in_progress: bool,
+    pub refresh_attempted: bool,
+    pub concurrency_limit: u32,
+    pub in_flight: u32,
+    pub queued: u32,
+    /// Named latency partitions seen so far.
+    pub partition_count: u32,
+    pub hedge_requests: u64,
+    pub hedges_sent: u64,
+    /// Hedges planned for requests not reported yet.
+    pub hedges_pending: u64,
+}
+
+#[no_mangle]
+pub extern "C" fn tc_client_state(
+    client: *const transport_core_client,
+    state: *mut tc_client_state_t,
+) {
+    guard((), || {
+        let snapshot = client_ref(client)?.snapshot();
+        if state.is_null() {
+            return Err(FfiError::null("state"));
+        }
+
+        unsafe {
+            *state = tc_client_state_t {
+                refresh_in_progress: snapshot.auth.refresh_in_progress,
+                refresh_attempted: snapshot.auth.refresh_attempted,
+                concurrency_limit: snapshot.concurrency_limit,
+                in_flight: snapshot.in_flight,
+                queued: snapshot.queued,
+                partition_count: u32::try_from(snapshot.partition_count).unwrap_or(u32::MAX),
+                hedge_requests: snapshot.hedge_stats.requests,
+                hedges_sent: snapshot.hedge_stats.hedges_sent,
+                hedges_pending: snapshot.hedge_stats.hedges_pending,
+            }
+        };
+        Ok(())
+    })
+}
+
+/// Exports the client's learned state as a versioned JSON document (see
+/// `client::ExportedState`), for hosts that persist it between
+/// processes. `now_ms` is the host's clock.
+///
+/// Returns NULL on error. The result MUST be freed with `tc_string_free`.
+#[no_mangle]
+pub extern "C" fn tc_client_export_state(
+    client: *const transport_core_client,
+    now_ms: u64,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let state = client_ref(client)?.export_state(now_ms);
+        Ok(into_c_string(state.to_json()))
+    })
+}
+
+/// Replaces the client's learned state with a document from
+/// `tc_client_export_state`. The policy and the in-flight / queued
+/// counts are kept; the imported concurrency limit and latency samples
+/// are fitted to the policy.
+///
+/// Returns false, leaving the client untouched, if the document is
+/// malformed or from an unsupported version.
+#[no_mangle]
+pub extern "C" fn tc_client_import_state(
+    client: *mut transport_core_client,
+    state: *const c_char,
+) -> bool {
+    guard(false, || {
+        let client = client_mut(client)?;
+        let state =
+            c_string(arg_ref(state, "state")?, "state")?.expect("state was checked for NULL");
+
+        ExportedState::from_json(&state)
+            .and_then(|state| client.import_state(state))
+            .map_err(|err| FfiError {
+                status: tc_status_t::TC_STATUS_INVALID_ARGUMENT,
+                message: err.to_string(),
+            })?;
+        Ok(true)
+    })
+}
+
+/* ============================
+ * Decision explanations
+ * ============================ */
+
+/// Turns decision explanations on or off (off by default).
+#[no_mangle]
+pub extern "C" fn tc_client_set_explain(client: *mut transport_core_client, explain: bool) {
+    guard((), || {
+        client_mut(client)?.set_explain(explain);
+        Ok(())
+    })
+}
+
+/// Rules evaluated by the last `tc_decide` as JSON:
+/// `{"steps": [{"rule": "attempt_limit", ...}, ...], "decision": ...}`.
+///
+/// Returns NULL when explanations are off or nothing was decided yet.
+/// The result MUST be freed with `tc_string_free`.
+#[no_mangle]
+pub extern "C" fn tc_last_explanation_json(client: *const transport_core_client) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        Ok(client_ref(client)?
+            .last_explanation()
+            .map_or(std::ptr::null_mut(), |e| into_c_string(e.to_json())))
+    })
+}
+
+/* ============================
+ * Observer
+ * ============================ */
+
+/// One decision, passed to a `tc_observer_fn`. Pointers are only valid
+/// during the callback.
+#[repr(C)]
+pub struct tc_decision_event_t {
+    /// A `tc_http_method_t` value.
+    pub method: c_int,
+    pub attempt: u8,
+    pub max_attempts: u8,
+    /// A `tc_priority_t` value.
+    pub priority: c_int,
+    /// Nullable.
+    pub partition: *const c_char,
+    /// A `tc_outcome_kind_t` value.
+    pub outcome_kind: c_int,
+    pub http_status: u16,
+    pub retry_after_ms: u32,
+    pub decision: tc_decision_t,
+    /// As from `tc_last_retry_after_ms`.
+    pub after_ms: u32,
+    /// A `tc_retry_reason_t` value.
+    pub retry_reason: u8,
+    /// A `tc_fail_reason_t` value.
+    pub fail_reason: u8,
+    pub fail_retryable: bool,
+    /// As from `tc_last_timeout_ms`.
+    pub timeout_ms: u32,
+}
+
+/// Called after every decision with the `userdata` it was registered
+/// with.
+#[allow(non_camel_case_types)]
+pub type tc_observer_fn =
+    Option<extern "C" fn(userdata: *mut c_void, event: *const tc_decision_event_t)>;
+
+struct CObserver {
+    callback: extern "C" fn(*mut c_void, *const tc_decision_event_t),
+    userdata: *mut c_void,
+}
+
+// The host promises the callback and userdata may be used from whichever
+// thread calls `tc_decide` (see `tc_client_set_observer`).
+unsafe impl Send for CObserver {}
+unsafe impl Sync for CObserver {}
+
+impl Observer for CObserver {
+    fn on_decision(&self, event: &DecisionEvent<'_>) {
+        let partition = event
+            .ctx
+            .partition
+            .as_deref()
+            .and_then(|p| CString::new(p).ok());
+
+        let (outcome_kind, http_status, retry_after_ms) = match event.outcome {
+            Outcome::NetworkError => (tc_outcome_kind_t::TC_OUTCOME_NETWORK_ERROR, 0, 0),
+            Outcome::TimeoutError => (tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR, 0, 0),
+            Outcome::HttpStatus(status) => (tc_outcome_kind_t::TC_OUTCOME_HTTP_STATUS, *status, 0),
+            Outcome::RateLimited { retry_after_ms } => (
+                tc_outcome_kind_t::TC_OUTCOME_RATE_LIMITED,
+                0,
+                retry_after_ms.unwrap_or(0),
+            ),
+            Outcome::Blocked => (tc_outcome_kind_t::TC_OUTCOME_BLOCKED, 0, 0),
+            Outcome::Captcha => (tc_outcome_kind_t::TC_OUTCOME_CAPTCHA, 0, 0),
+        };
+
+        let (decision, after_ms, retry_reason, fail_reason, fail_retryable) = match event.decision {
+            Decision::Proceed => (tc_decision_t::TC_DECISION_PROCEED, 0, 0, 0, false),
+            Decision::Retry { after_ms, reason } => (
+                tc_decision_t::TC_DECISION_RETRY,
+                *after_ms,
+                *reason as u8,
+                0,
+                false,
+            ),
+            Decision::RefreshAndRetry { after_ms } => (
+                tc_decision_t::TC_DECISION_REFRESH_AND_RETRY,
+                *after_ms,
+                0,
+                0,
+                false,
+            ),
+            Decision::Fail { reason, retryable } => (
+                tc_decision_t::TC_DECISION_FAIL,
+                0,
+                0,
+                *reason as u8,
+                *retryable,
+            ),
+        };
+
+        let c_event = tc_decision_event_t {
+            method: method_to_c(&event.ctx.method) as c_int,
+            attempt: event.ctx.attempt,
+            max_attempts: event.ctx.max_attempts,
+            priority: priority_to_c(event.ctx.priority) as c_int,
+            partition: partition.as_ref().map_or(std::ptr::null(), |p| p.as_ptr()),
+            outcome_kind: outcome_kind as c_int,
+            http_status,
+            retry_after_ms,
+            decision,
+            after_ms,
+            retry_reason,
+            fail_reason,
+            fail_retryable,
+            timeout_ms: event.timeout_ms.unwrap_or(0),
+        };
+
+        (self.callback)(self.userdata, &c_event);
     }
+}
 
-    let client = unsafe { &*client };
+fn method_to_c(method: &HttpMethod) -> tc_http_method_t {
+    match method {
+        HttpMethod::GET => tc_http_method_t::TC_HTTP_GET,
+        HttpMethod::POST => tc_http_method_t::TC_HTTP_POST,
+        HttpMethod::PUT => tc_http_method_t::TC_HTTP_PUT,
+        HttpMethod::DELETE => tc_http_method_t::TC_HTTP_DELETE,
+        HttpMethod::HEAD => tc_http_method_t::TC_HTTP_HEAD,
+        HttpMethod::OPTIONS => tc_http_method_t::TC_HTTP_OPTIONS,
+    }
+}
 
-    match &client.last_decision {
-        Some(Decision::Fail { retryable, .. }) => *retryable,
-        _ => false,
+fn priority_to_c(priority: Priority) -> tc_priority_t {
+    match priority {
+        Priority::Normal => tc_priority_t::TC_PRIORITY_NORMAL,
+        Priority::Critical => tc_priority_t::TC_PRIORITY_CRITICAL,
+        Priority::Background => tc_priority_t::TC_PRIORITY_BACKGROUND,
     }
 }
+
+/// Registers `observer`, called after every decision the client makes
+/// (including through `tc_decide_json`) with `userdata`. NULL removes
+/// the observer.
+///
+/// The callback runs on the thread calling `tc_decide`, before it
+/// returns, and MUST NOT call back into this client.
+#[no_mangle]
+pub extern "C" fn tc_client_set_observer(
+    client: *mut transport_core_client,
+    observer: tc_observer_fn,
+    userdata: *mut c_void,
+) {
+    guard((), || {
+        let client = client_mut(client)?;
+
+        match observer {
+            Some(callback) => client.set_observer(Arc::new(CObserver { callback, userdata })),
+            None => client.clear_observer(),
+        }
+        Ok(())
+    })
+}
+
+/* ============================
+ * Metrics
+ * ============================ */
+
+/// Decision counters and histograms as JSON, shaped after the Prometheus
+/// data model:
+///
+/// ```json
+/// {"counters": [{"name": "tc_decisions_total",
+///                "labels": {"partition": "", "decision": "retry", "reason": "timeout"},
+///                "value": 3}, ...],
+///  "histograms": [{"name": "tc_retry_delay_ms", "labels": {"partition": ""},
+///                  "buckets": [{"le": 0, "count": 0}, ..., {"le": null, "count": 3}],
+///                  "sum": 1200, "count": 3}]}
+/// ```
+///
+/// Bucket counts are cumulative and `"le": null` is `+Inf`. The result
+/// MUST be freed with `tc_string_free`.
+#[no_mangle]
+pub extern "C" fn tc_client_metrics_json(client: *const transport_core_client) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        Ok(into_c_string(
+            client_ref(client)?.metrics_snapshot().to_json(),
+        ))
+    })
+}
+
+/* ============================
+ * Recording
+ * ============================ */
+
+/// Turns recording of `tc_decide` calls on or off (off by default).
+/// Turning it on starts the log with the client's policy and auth state.
+#[no_mangle]
+pub extern "C" fn tc_client_set_recording(client: *mut transport_core_client, recording: bool) {
+    guard((), || {
+        client_mut(client)?.set_recording(recording);
+        Ok(())
+    })
+}
+
+/// Drains the decisions recorded since the last call as JSON lines, one
+/// entry per line; concatenated, the chunks form one log for
+/// `transport-core replay`. Returns an empty string when nothing was
+/// recorded. The result MUST be freed with `tc_string_free`.
+#[no_mangle]
+pub extern "C" fn tc_client_take_recording(client: *mut transport_core_client) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        Ok(into_c_string(client_mut(client)?.take_recording()))
+    })
+}
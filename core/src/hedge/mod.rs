@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{model::RequestContext, retry::is_idempotent, stats::LatencyStats};
+
+/// When the host should send a duplicate of an in-flight attempt.
+///
+/// Hedging is disabled by default (`max_hedges == 0`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgePolicy {
+    /// Maximum duplicate attempts per request, not counting the original.
+    pub max_hedges: u8,
+    /// Delay without a response before each hedge is sent.
+    pub delay_ms: u32,
+    /// When set (1..=100), hedge once the request has been outstanding
+    /// for this latency percentile of its partition instead of
+    /// `delay_ms`, which remains the delay until enough samples exist.
+    pub delay_percentile: u8,
+    /// Samples a partition needs before `delay_percentile` applies (at
+    /// least one).
+    pub min_samples: usize,
+    /// When set, hedges planned stay within this percentage of reported
+    /// requests, so hedging cannot multiply load on a slow upstream.
+    pub budget_percent: u32,
+}
+
+/// Running totals of planned hedges and host-reported results.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HedgeStats {
+    pub requests: u64,
+    pub hedges_sent: u64,
+    /// Hedges planned for requests not reported yet. They count against
+    /// the budget from the moment they are planned, so a burst of
+    /// requests cannot all hedge before any result comes back.
+    #[serde(default)]
+    pub hedges_pending: u64,
+}
+
+/// Returns the delay after which the host should send the next hedge,
+/// or `None` if no further hedge is allowed.
+///
+/// Only idempotent methods are hedged; an idempotency key does not make
+/// a duplicate `POST` safe to race.
+///
+/// Without latency samples or reported results this always uses
+/// `delay_ms` and ignores the budget; see [`plan_hedge_with_stats`].
+pub fn plan_hedge(ctx: &RequestContext, hedges_sent: u8, policy: &HedgePolicy) -> Option<u32> {
+    if !is_idempotent(&ctx.method) {
+        return None;
+    }
+
+    if hedges_sent >= policy.max_hedges {
+        return None;
+    }
+
+    Some(policy.delay_ms)
+}
+
+/// [`plan_hedge`] with the percentile delay taken from `latency` and
+/// the budget checked against `stats`.
+pub fn plan_hedge_with_stats(
+    ctx: &RequestContext,
+    hedges_sent: u8,
+    policy: &HedgePolicy,
+    latency: &LatencyStats,
+    stats: &HedgeStats,
+) -> Option<u32> {
+    let delay_ms = plan_hedge(ctx, hedges_sent, policy)?;
+
+    if policy.budget_percent > 0 && !stats.within_budget(policy.budget_percent) {
+        return None;
+    }
+
+    if policy.delay_percentile == 0 {
+        return Some(delay_ms);
+    }
+
+    let percentile = latency
+        .partition(ctx.partition.as_deref())
+        .filter(|stats| stats.samples().count() >= policy.min_samples.max(1))
+        .and_then(|stats| stats.percentile_ms(policy.delay_percentile));
+    Some(percentile.unwrap_or(delay_ms))
+}
+
+impl HedgeStats {
+    /// Counts a hedge planned for a request still in flight.
+    pub fn plan(&mut self) {
+        self.hedges_pending = self.hedges_pending.saturating_add(1);
+    }
+
+    /// Counts a finished request and settles what was planned for it: the
+    /// hedges it sent, and at most one more armed but not needed.
+    pub fn record(&mut self, hedges_sent: u8) {
+        self.requests = self.requests.saturating_add(1);
+        self.hedges_sent = self.hedges_sent.saturating_add(u64::from(hedges_sent));
+        self.hedges_pending = self
+            .hedges_pending
+            .saturating_sub(u64::from(hedges_sent) + 1);
+    }
+
+    /// Whether one more hedge keeps hedges sent or pending within
+    /// `budget_percent` of requests, counting the request being planned.
+    fn within_budget(&self, budget_percent: u32) -> bool {
+        let allowed = (u128::from(self.requests) + 1) * u128::from(budget_percent);
+        let hedges = u128::from(self.hedges_sent) + u128::from(self.hedges_pending) + 1;
+        hedges * 100 <= allowed
+    }
+}
@@ -0,0 +1,115 @@
+//! JSON-in/JSON-out entry point for scripting hosts.
+//!
+//! Input:
+//!
+//! ```json
+//! {
+//!   "ctx": { "method": "GET", "attempt": 1, "max_attempts": 3,
+//!            "idempotency_key": null, "allow_non_idempotent_retry": false },
+//!   "outcome": { "RateLimited": { "retry_after_ms": 3000 } },
+//!   "auth_decision": "RefreshAndRetry",
+//!   "refresh_result": null,
+//!   "auth_state": { "refresh_in_progress": false, "refresh_attempted": false },
+//!   "policy": {}
+//! }
+//! ```
+//!
+//! Only `ctx` and `outcome` are required. Output is either
+//! `{ "decision": ..., "auth_state": ... }` or `{ "error": "..." }`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{AuthDecision, AuthState},
+    client::Client,
+    decision::decide_with_policy,
+    model::{Decision, Outcome, RequestContext},
+    policy::Policy,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecideRequest {
+    pub ctx: RequestContext,
+    pub outcome: Outcome,
+    #[serde(default)]
+    pub auth_decision: Option<AuthDecision>,
+    #[serde(default)]
+    pub refresh_result: Option<bool>,
+    /// Ignored by `Client::decide_json`, which uses the client's state.
+    #[serde(default)]
+    pub auth_state: Option<AuthState>,
+    /// Ignored by `Client::decide_json`, which uses the client's policy.
+    #[serde(default)]
+    pub policy: Option<Policy>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DecideResponse {
+    Ok {
+        decision: Decision,
+        auth_state: AuthState,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// Stateless JSON entry point. Hosts keep state between calls by passing
+/// the returned `auth_state` back in with the next request.
+pub fn decide_json(input: &str) -> String {
+    let response = match serde_json::from_str::<DecideRequest>(input) {
+        Ok(req) => {
+            let mut auth_state = req.auth_state.unwrap_or_default();
+            let policy = req.policy.unwrap_or_default();
+            let decision = decide_with_policy(
+                &req.ctx,
+                req.outcome,
+                req.auth_decision,
+                &mut auth_state,
+                req.refresh_result,
+                &policy,
+            );
+
+            DecideResponse::Ok {
+                decision,
+                auth_state,
+            }
+        }
+        Err(err) => DecideResponse::Error {
+            error: err.to_string(),
+        },
+    };
+
+    response.to_json()
+}
+
+/// Decides with the client's state and policy. See
+/// [`Client::decide_json`].
+pub(crate) fn decide_with_client(client: &mut Client, input: &str) -> DecideResponse {
+    match serde_json::from_str::<DecideRequest>(input) {
+        Ok(req) => {
+            let decision =
+                client.decide(&req.ctx, req.outcome, req.auth_decision, req.refresh_result);
+
+            DecideResponse::Ok {
+                decision,
+                auth_state: client.auth_state().clone(),
+            }
+        }
+        Err(err) => DecideResponse::Error {
+            error: err.to_string(),
+        },
+    }
+}
+
+impl DecideResponse {
+    pub fn is_error(&self) -> bool {
+        matches!(self, DecideResponse::Error { .. })
+    }
+
+    pub fn to_json(&self) -> String {
+        // Serializing these types cannot fail: no maps with non-string keys.
+        serde_json::to_string(self).expect("decision response is serializable")
+    }
+}
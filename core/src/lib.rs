@@ -1,7 +1,33 @@
+// Emits a `tracing` event when the `tracing` feature is enabled and
+// expands to nothing otherwise, so call sites need no `cfg`.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::$level!($($arg)+);
+    };
+}
+
 pub mod auth;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod client;
+pub mod concurrency;
 pub mod decision;
 pub mod error;
+pub mod hedge;
+pub mod json;
+pub mod metrics;
 pub mod model;
+pub mod observer;
+pub mod policy;
+pub mod replay;
 pub mod retry;
+pub mod simulation;
+pub mod stats;
 
 pub mod ffi;
+
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,317 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{Decision, FailReason, RequestContext, RetryReason},
+    retry::clamp_retry_after,
+};
+
+/// Upper bounds of the retry delay histogram buckets, in milliseconds.
+pub const DELAY_BUCKETS_MS: [u32; 11] = [
+    0, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 120_000,
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Histogram {
+    /// Non-cumulative counts per bucket; one extra slot for `+Inf`.
+    buckets: [u64; DELAY_BUCKETS_MS.len() + 1],
+    sum: u64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: u32) {
+        let bucket = DELAY_BUCKETS_MS
+            .iter()
+            .position(|&le| value <= le)
+            .unwrap_or(DELAY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.sum += u64::from(value);
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct PartitionMetrics {
+    /// Keyed by (decision, reason).
+    decisions: BTreeMap<(&'static str, &'static str), u64>,
+    retries_by_attempt: BTreeMap<u8, u64>,
+    retry_delay_ms: Histogram,
+    /// Keyed by result.
+    auth_refreshes: BTreeMap<&'static str, u64>,
+    hint_clamps: u64,
+}
+
+/// Counters and histograms over a client's decisions, kept per latency
+/// partition (`""` for the default one).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecisionMetrics {
+    partitions: BTreeMap<String, PartitionMetrics>,
+}
+
+impl DecisionMetrics {
+    /// Counts one decision. `retry_after_ms` is the outcome's
+    /// `Retry-After` hint, if it carried one.
+    pub fn record(
+        &mut self,
+        ctx: &RequestContext,
+        retry_after_ms: Option<u32>,
+        refresh_result: Option<bool>,
+        decision: &Decision,
+    ) {
+        let partition = ctx.partition.as_deref().unwrap_or("");
+        if !self.partitions.contains_key(partition) {
+            self.partitions
+                .insert(partition.to_string(), PartitionMetrics::default());
+        }
+        let metrics = self.partitions.get_mut(partition).expect("just inserted");
+
+        *metrics.decisions.entry(labels(decision)).or_insert(0) += 1;
+
+        match decision {
+            Decision::Retry { after_ms, reason } => {
+                *metrics.retries_by_attempt.entry(ctx.attempt).or_insert(0) += 1;
+                metrics.retry_delay_ms.observe(*after_ms);
+
+                if *reason == RetryReason::RateLimited
+                    && retry_after_ms.is_some_and(|ms| clamp_retry_after(ms) != ms)
+                {
+                    metrics.hint_clamps += 1;
+                }
+            }
+            Decision::RefreshAndRetry { after_ms } => {
+                *metrics.retries_by_attempt.entry(ctx.attempt).or_insert(0) += 1;
+                metrics.retry_delay_ms.observe(*after_ms);
+
+                let result = match refresh_result {
+                    Some(true) => "succeeded",
+                    _ => "requested",
+                };
+                *metrics.auth_refreshes.entry(result).or_insert(0) += 1;
+            }
+            Decision::Fail {
+                reason: FailReason::AuthFailed,
+                ..
+            } if refresh_result == Some(false) => {
+                *metrics.auth_refreshes.entry("failed").or_insert(0) += 1;
+            }
+            Decision::Proceed | Decision::Fail { .. } => {}
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut counters = Vec::new();
+        let mut histograms = Vec::new();
+
+        for (partition, metrics) in &self.partitions {
+            for (&(decision, reason), &value) in &metrics.decisions {
+                counters.push(Counter {
+                    name: "tc_decisions_total".to_string(),
+                    labels: label_map(partition, &[("decision", decision), ("reason", reason)]),
+                    value,
+                });
+            }
+            for (attempt, &value) in &metrics.retries_by_attempt {
+                counters.push(Counter {
+                    name: "tc_retries_total".to_string(),
+                    labels: label_map(partition, &[("attempt", &attempt.to_string())]),
+                    value,
+                });
+            }
+            for (&result, &value) in &metrics.auth_refreshes {
+                counters.push(Counter {
+                    name: "tc_auth_refreshes_total".to_string(),
+                    labels: label_map(partition, &[("result", result)]),
+                    value,
+                });
+            }
+            counters.push(Counter {
+                name: "tc_retry_after_clamped_total".to_string(),
+                labels: label_map(partition, &[]),
+                value: metrics.hint_clamps,
+            });
+
+            let delay = &metrics.retry_delay_ms;
+            let mut cumulative = 0;
+            let buckets = delay
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(i, count)| {
+                    cumulative += count;
+                    Bucket {
+                        le: DELAY_BUCKETS_MS.get(i).copied(),
+                        count: cumulative,
+                    }
+                })
+                .collect();
+            histograms.push(HistogramSnapshot {
+                name: "tc_retry_delay_ms".to_string(),
+                labels: label_map(partition, &[]),
+                buckets,
+                sum: delay.sum,
+                count: delay.count,
+            });
+        }
+
+        MetricsSnapshot {
+            counters,
+            histograms,
+        }
+    }
+}
+
+fn labels(decision: &Decision) -> (&'static str, &'static str) {
+    match decision {
+        Decision::Proceed => ("proceed", ""),
+        Decision::Retry { reason, .. } => (
+            "retry",
+            match reason {
+                RetryReason::NetworkError => "network_error",
+                RetryReason::Timeout => "timeout",
+                RetryReason::RateLimited => "rate_limited",
+                RetryReason::AuthExpired => "auth_expired",
+            },
+        ),
+        Decision::RefreshAndRetry { .. } => ("refresh_and_retry", ""),
+        Decision::Fail { reason, .. } => (
+            "fail",
+            match reason {
+                FailReason::MaxAttemptsExceeded => "max_attempts_exceeded",
+                FailReason::AuthFailed => "auth_failed",
+                FailReason::HardBlocked => "hard_blocked",
+                FailReason::Shed => "shed",
+                FailReason::Unknown => "unknown",
+            },
+        ),
+    }
+}
+
+fn label_map(partition: &str, extra: &[(&str, &str)]) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    labels.insert("partition".to_string(), partition.to_string());
+    for (name, value) in extra {
+        labels.insert(name.to_string(), value.to_string());
+    }
+    labels
+}
+
+/// Point-in-time copy of a client's metrics, shaped after the
+/// Prometheus data model: every sample is a metric name, a label set and
+/// a value. Bucket counts are cumulative; `le: None` is `+Inf`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub counters: Vec<Counter>,
+    pub histograms: Vec<HistogramSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counter {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistogramSnapshot {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub buckets: Vec<Bucket>,
+    pub sum: u64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bucket {
+    pub le: Option<u32>,
+    pub count: u64,
+}
+
+impl MetricsSnapshot {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("metrics serialize to JSON")
+    }
+
+    /// Renders the snapshot in the Prometheus text exposition format.
+    ///
+    /// Samples are grouped by metric name, in order of first appearance,
+    /// since the format needs each family in one block under a single
+    /// `# TYPE` line while the snapshot lists them per partition.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        for name in family_names(self.counters.iter().map(|c| &c.name)) {
+            writeln!(out, "# TYPE {name} counter").unwrap();
+            for counter in self.counters.iter().filter(|c| &c.name == name) {
+                writeln!(
+                    out,
+                    "{}{} {}",
+                    counter.name,
+                    render_labels(&counter.labels, None),
+                    counter.value
+                )
+                .unwrap();
+            }
+        }
+
+        for name in family_names(self.histograms.iter().map(|h| &h.name)) {
+            writeln!(out, "# TYPE {name} histogram").unwrap();
+            for histogram in self.histograms.iter().filter(|h| &h.name == name) {
+                for bucket in &histogram.buckets {
+                    let le = bucket.le.map_or("+Inf".to_string(), |le| le.to_string());
+                    writeln!(
+                        out,
+                        "{}_bucket{} {}",
+                        histogram.name,
+                        render_labels(&histogram.labels, Some(&le)),
+                        bucket.count
+                    )
+                    .unwrap();
+                }
+                let labels = render_labels(&histogram.labels, None);
+                writeln!(out, "{}_sum{} {}", histogram.name, labels, histogram.sum).unwrap();
+                writeln!(
+                    out,
+                    "{}_count{} {}",
+                    histogram.name, labels, histogram.count
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+}
+
+/// Distinct names, in order of first appearance.
+fn family_names<'a>(names: impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut families: Vec<&String> = Vec::new();
+    for name in names {
+        if !families.contains(&name) {
+            families.push(name);
+        }
+    }
+    families
+}
+
+fn render_labels(labels: &BTreeMap<String, String>, le: Option<&str>) -> String {
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect();
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HttpMethod {
     GET,
     POST,
@@ -17,9 +17,34 @@ pub struct RequestContext {
     pub max_attempts: u8,
     pub idempotency_key: Option<String>,
     pub allow_non_idempotent_retry: bool,
+    /// Host-chosen key (e.g. upstream host) that latency statistics are
+    /// tracked under. `None` uses the client-wide default partition.
+    #[serde(default)]
+    pub partition: Option<String>,
+    /// Only a [`Client`](crate::client::Client) sheds by priority, since
+    /// load is client state; the stateless [`decide`](crate::decision::decide)
+    /// functions and [`json::decide_json`](crate::json::decide_json)
+    /// accept it but ignore it.
+    #[serde(default)]
+    pub priority: Priority,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How important a request is when the client is under load.
+/// Lower priorities are shed first, and the ordering follows that.
+///
+/// There are deliberately no discriminants: the C ABI numbers priorities
+/// differently (see `ffi::tc_priority_t`) and converts explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum Priority {
+    /// Background work that may be dropped to protect other requests.
+    Background,
+    #[default]
+    Normal,
+    /// User-facing work that is never shed.
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Outcome {
     // Transport-level
     NetworkError,
@@ -59,5 +84,6 @@ pub enum FailReason {
     MaxAttemptsExceeded = 1,
     AuthFailed = 2,
     HardBlocked = 3,
+    Shed = 4,
     Unknown = 255,
 }
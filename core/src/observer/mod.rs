@@ -0,0 +1,53 @@
+use std::{fmt, sync::Arc};
+
+use crate::{
+    auth::AuthDecision,
+    model::{Decision, Outcome, RequestContext},
+};
+
+/// Everything `Client::decide` saw and produced for one decision.
+#[derive(Debug, Clone, Copy)]
+pub struct DecisionEvent<'a> {
+    pub ctx: &'a RequestContext,
+    pub outcome: &'a Outcome,
+    pub auth_decision: Option<&'a AuthDecision>,
+    pub refresh_result: Option<bool>,
+    pub decision: &'a Decision,
+    /// Recommended timeout for a retry, as from `Client::last_timeout_ms`.
+    pub timeout_ms: Option<u32>,
+}
+
+/// Receives every decision a client makes, e.g. to count decisions by
+/// type and reason or to annotate a tracing span. Implement it once per
+/// host instead of in every call site.
+///
+/// Called synchronously from `decide`; keep it cheap.
+pub trait Observer: Send + Sync {
+    fn on_decision(&self, event: &DecisionEvent<'_>);
+}
+
+/// Optional observer held by a client.
+#[derive(Clone, Default)]
+pub(crate) struct ObserverSlot(Option<Arc<dyn Observer>>);
+
+impl ObserverSlot {
+    pub(crate) fn set(&mut self, observer: Option<Arc<dyn Observer>>) {
+        self.0 = observer;
+    }
+
+    pub(crate) fn is_set(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(crate) fn notify(&self, event: &DecisionEvent<'_>) {
+        if let Some(observer) = &self.0 {
+            observer.on_decision(event);
+        }
+    }
+}
+
+impl fmt::Debug for ObserverSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
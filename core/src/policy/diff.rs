@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{AuthDecision, AuthState},
+    decision::decide_with_policy,
+    error::{classify_http_status, ErrorCategory},
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+};
+
+use super::Policy;
+
+/// The inputs [`diff_policies`] enumerates: the product of every field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputSpace {
+    pub methods: Vec<HttpMethod>,
+    /// Attempts `1..=max_attempts` are tried, the last one exhausted.
+    pub max_attempts: u8,
+    /// Raw statuses tried as `Outcome::HttpStatus`, next to every
+    /// semantic outcome.
+    pub statuses: Vec<u16>,
+    /// Retry-After hints tried with `Outcome::RateLimited`, besides none.
+    pub retry_after_ms: Vec<u32>,
+}
+
+impl Default for InputSpace {
+    fn default() -> Self {
+        Self {
+            methods: vec![
+                HttpMethod::GET,
+                HttpMethod::POST,
+                HttpMethod::PUT,
+                HttpMethod::DELETE,
+                HttpMethod::HEAD,
+                HttpMethod::OPTIONS,
+            ],
+            max_attempts: 3,
+            statuses: vec![400, 401, 403, 404, 408, 409, 422, 429, 500, 503],
+            // Either side of the rate-limit backoff and of the clamp.
+            retry_after_ms: vec![
+                0,
+                1,
+                500,
+                1_499,
+                1_500,
+                1_501,
+                10_000,
+                119_999,
+                120_000,
+                120_001,
+                u32::MAX,
+            ],
+        }
+    }
+}
+
+/// One point of the input space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffInput {
+    pub ctx: RequestContext,
+    pub outcome: Outcome,
+    pub auth_state: AuthState,
+    pub auth_decision: Option<AuthDecision>,
+    pub refresh_result: Option<bool>,
+}
+
+/// An input the two policies decide differently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionDiff {
+    pub input: DiffInput,
+    pub old: Decision,
+    pub new: Decision,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyDiff {
+    /// Number of inputs compared.
+    pub inputs: usize,
+    pub differences: Vec<DecisionDiff>,
+}
+
+impl PolicyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("diff serializes to JSON")
+    }
+}
+
+impl InputSpace {
+    /// Every input in the space. Auth state, auth decision and refresh
+    /// result are only varied for auth-error statuses, the only
+    /// outcomes that read them.
+    pub fn inputs(&self) -> Vec<DiffInput> {
+        let mut outcomes = vec![
+            Outcome::NetworkError,
+            Outcome::TimeoutError,
+            Outcome::Blocked,
+            Outcome::Captcha,
+            Outcome::RateLimited {
+                retry_after_ms: None,
+            },
+        ];
+        outcomes.extend(self.retry_after_ms.iter().map(|&ms| Outcome::RateLimited {
+            retry_after_ms: Some(ms),
+        }));
+        outcomes.extend(self.statuses.iter().map(|&s| Outcome::HttpStatus(s)));
+
+        let mut auth_inputs = Vec::new();
+        for refresh_attempted in [false, true] {
+            for auth_decision in [
+                None,
+                Some(AuthDecision::RefreshAndRetry),
+                Some(AuthDecision::Fail),
+            ] {
+                for refresh_result in [None, Some(true), Some(false)] {
+                    let auth_state = AuthState {
+                        refresh_in_progress: false,
+                        refresh_attempted,
+                    };
+                    auth_inputs.push((auth_state, auth_decision, refresh_result));
+                }
+            }
+        }
+        let no_auth = [(AuthState::new(), None, None)];
+
+        let mut inputs = Vec::new();
+        for method in &self.methods {
+            // Keys and the override only matter to non-idempotent
+            // methods, but are varied for all to stay engine-agnostic.
+            for (idempotency_key, allow_non_idempotent_retry) in [
+                (None, false),
+                (None, true),
+                (Some("diff-key".to_string()), false),
+                (Some("diff-key".to_string()), true),
+            ] {
+                for attempt in 1..=self.max_attempts {
+                    let ctx = RequestContext {
+                        method: method.clone(),
+                        attempt,
+                        max_attempts: self.max_attempts,
+                        idempotency_key: idempotency_key.clone(),
+                        allow_non_idempotent_retry,
+                        partition: None,
+                        priority: Priority::Normal,
+                    };
+
+                    for outcome in &outcomes {
+                        let auth = match outcome {
+                            Outcome::HttpStatus(status)
+                                if classify_http_status(*status) == ErrorCategory::AuthError =>
+                            {
+                                &auth_inputs[..]
+                            }
+                            _ => &no_auth[..],
+                        };
+
+                        for (auth_state, auth_decision, refresh_result) in auth {
+                            inputs.push(DiffInput {
+                                ctx: ctx.clone(),
+                                outcome: outcome.clone(),
+                                auth_state: auth_state.clone(),
+                                auth_decision: *auth_decision,
+                                refresh_result: *refresh_result,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        inputs
+    }
+}
+
+/// Decides every input of `space` under both policies and reports those
+/// decided differently.
+///
+/// Only [`decide_with_policy`] is compared: load shedding and timeouts
+/// depend on client state rather than on inputs. Differences between
+/// engine versions are found by replaying a recording instead (see
+/// [`crate::replay`]).
+pub fn diff_policies(old: &Policy, new: &Policy, space: &InputSpace) -> PolicyDiff {
+    let inputs = space.inputs();
+    let mut diff = PolicyDiff {
+        inputs: inputs.len(),
+        differences: Vec::new(),
+    };
+
+    for input in inputs {
+        let decide = |policy: &Policy| {
+            decide_with_policy(
+                &input.ctx,
+                input.outcome.clone(),
+                input.auth_decision,
+                &mut input.auth_state.clone(),
+                input.refresh_result,
+                policy,
+            )
+        };
+
+        let (old, new) = (decide(old), decide(new));
+        if old != new {
+            diff.differences.push(DecisionDiff { input, old, new });
+        }
+    }
+    diff
+}
@@ -0,0 +1,22 @@
+mod diff;
+
+pub use diff::{diff_policies, DecisionDiff, DiffInput, InputSpace, PolicyDiff};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    concurrency::ConcurrencyPolicy, hedge::HedgePolicy, retry::RetryPolicy, stats::TimeoutPolicy,
+};
+
+/// Tunable engine behavior.
+///
+/// `Policy::default()` reproduces the SPEC v1 decisions exactly, so
+/// hosts only opt into new behavior explicitly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub retry: RetryPolicy,
+    pub hedge: HedgePolicy,
+    pub timeout: TimeoutPolicy,
+    pub concurrency: ConcurrencyPolicy,
+}
@@ -0,0 +1,513 @@
+//! Native Python extension module `transport_core._native`.
+//!
+//! The classes wrap the Rust types directly, so there is no struct layout
+//! to keep in sync with the C header. Type stubs live next to the Python
+//! package in `bindings/python/transport_core/_native.pyi`;
+//! `tests/test_native.py` checks them against the built module.
+
+// `#[pymethods]` expands `PyResult` returns into a conversion clippy
+// considers useless.
+#![allow(clippy::useless_conversion)]
+
+use std::sync::Arc;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    auth::AuthDecision,
+    client::{Client as CoreClient, ExportedState},
+    model::{self, HttpMethod, Priority},
+    observer::{self, Observer},
+    policy::Policy,
+};
+
+fn parse_method(method: &str) -> PyResult<HttpMethod> {
+    Ok(match method {
+        "GET" => HttpMethod::GET,
+        "POST" => HttpMethod::POST,
+        "PUT" => HttpMethod::PUT,
+        "DELETE" => HttpMethod::DELETE,
+        "HEAD" => HttpMethod::HEAD,
+        "OPTIONS" => HttpMethod::OPTIONS,
+        other => return Err(PyValueError::new_err(format!("unknown method: {}", other))),
+    })
+}
+
+fn parse_priority(priority: &str) -> PyResult<Priority> {
+    Ok(match priority {
+        "Background" => Priority::Background,
+        "Normal" => Priority::Normal,
+        "Critical" => Priority::Critical,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown priority: {}",
+                other
+            )))
+        }
+    })
+}
+
+fn parse_policy(policy_json: &str) -> PyResult<Policy> {
+    serde_json::from_str(policy_json).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn parse_auth_decision(auth_decision: Option<&str>) -> PyResult<Option<AuthDecision>> {
+    Ok(match auth_decision {
+        None | Some("Fail") => None,
+        Some("RefreshAndRetry") => Some(AuthDecision::RefreshAndRetry),
+        Some(other) => {
+            return Err(PyValueError::new_err(format!(
+                "unknown auth decision: {}",
+                other
+            )))
+        }
+    })
+}
+
+/// Converts one of the crate's JSON documents into Python objects.
+fn json_to_py(py: Python<'_>, json: &str) -> PyResult<PyObject> {
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (json,))?
+        .unbind())
+}
+
+#[pyclass(module = "transport_core._native")]
+#[derive(Clone)]
+pub struct RequestContext {
+    inner: model::RequestContext,
+}
+
+#[pymethods]
+impl RequestContext {
+    #[new]
+    #[pyo3(signature = (
+        method,
+        attempt,
+        max_attempts,
+        idempotency_key = None,
+        allow_non_idempotent_retry = false,
+        partition = None,
+        priority = "Normal",
+    ))]
+    fn new(
+        method: &str,
+        attempt: u8,
+        max_attempts: u8,
+        idempotency_key: Option<String>,
+        allow_non_idempotent_retry: bool,
+        partition: Option<String>,
+        priority: &str,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: model::RequestContext {
+                method: parse_method(method)?,
+                attempt,
+                max_attempts,
+                idempotency_key,
+                allow_non_idempotent_retry,
+                partition,
+                priority: parse_priority(priority)?,
+            },
+        })
+    }
+
+    #[getter]
+    fn attempt(&self) -> u8 {
+        self.inner.attempt
+    }
+
+    #[getter]
+    fn max_attempts(&self) -> u8 {
+        self.inner.max_attempts
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RequestContext({:?})", self.inner)
+    }
+}
+
+#[pyclass(module = "transport_core._native")]
+#[derive(Clone)]
+pub struct Outcome {
+    inner: model::Outcome,
+}
+
+#[pymethods]
+impl Outcome {
+    #[staticmethod]
+    fn network_error() -> Self {
+        Self {
+            inner: model::Outcome::NetworkError,
+        }
+    }
+
+    #[staticmethod]
+    fn timeout() -> Self {
+        Self {
+            inner: model::Outcome::TimeoutError,
+        }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (retry_after_ms = None))]
+    fn rate_limited(retry_after_ms: Option<u32>) -> Self {
+        Self {
+            inner: model::Outcome::RateLimited { retry_after_ms },
+        }
+    }
+
+    #[staticmethod]
+    fn blocked() -> Self {
+        Self {
+            inner: model::Outcome::Blocked,
+        }
+    }
+
+    #[staticmethod]
+    fn captcha() -> Self {
+        Self {
+            inner: model::Outcome::Captcha,
+        }
+    }
+
+    #[staticmethod]
+    fn http_status(status: u16) -> Self {
+        Self {
+            inner: model::Outcome::HttpStatus(status),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Outcome.{:?}", self.inner)
+    }
+}
+
+#[pyclass(module = "transport_core._native", frozen)]
+#[derive(Clone)]
+pub struct Decision {
+    inner: model::Decision,
+    timeout_ms: Option<u32>,
+}
+
+#[pymethods]
+impl Decision {
+    /// `"PROCEED"`, `"RETRY"`, `"REFRESH_AND_RETRY"` or `"FAIL"`.
+    #[getter]
+    fn action(&self) -> &'static str {
+        match self.inner {
+            model::Decision::Proceed => "PROCEED",
+            model::Decision::Retry { .. } => "RETRY",
+            model::Decision::RefreshAndRetry { .. } => "REFRESH_AND_RETRY",
+            model::Decision::Fail { .. } => "FAIL",
+        }
+    }
+
+    #[getter]
+    fn after_ms(&self) -> Option<u32> {
+        match self.inner {
+            model::Decision::Retry { after_ms, .. }
+            | model::Decision::RefreshAndRetry { after_ms } => Some(after_ms),
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn retry_reason(&self) -> Option<String> {
+        match self.inner {
+            model::Decision::Retry { reason, .. } => Some(format!("{:?}", reason)),
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn fail_reason(&self) -> Option<String> {
+        match self.inner {
+            model::Decision::Fail { reason, .. } => Some(format!("{:?}", reason)),
+            _ => None,
+        }
+    }
+
+    #[getter]
+    fn retryable(&self) -> bool {
+        matches!(
+            self.inner,
+            model::Decision::Fail {
+                retryable: true,
+                ..
+            }
+        )
+    }
+
+    #[getter]
+    fn timeout_ms(&self) -> Option<u32> {
+        self.timeout_ms
+    }
+
+    fn __eq__(&self, other: &Decision) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Decision.{:?}", self.inner)
+    }
+}
+
+/// Passed to an observer after every decision.
+#[pyclass(module = "transport_core._native", frozen, get_all)]
+pub struct DecisionEvent {
+    ctx: RequestContext,
+    outcome: Outcome,
+    /// `"RefreshAndRetry"`, `"Fail"` or `None`.
+    auth_decision: Option<String>,
+    refresh_result: Option<bool>,
+    decision: Decision,
+}
+
+#[pymethods]
+impl DecisionEvent {
+    fn __repr__(&self) -> String {
+        format!(
+            "DecisionEvent({:?}, {:?} -> {:?})",
+            self.ctx.inner, self.outcome.inner, self.decision.inner
+        )
+    }
+}
+
+/// Calls a Python callable with a [`DecisionEvent`].
+struct PyObserver(PyObject);
+
+impl Observer for PyObserver {
+    fn on_decision(&self, event: &observer::DecisionEvent<'_>) {
+        // `decide` runs with the GIL released.
+        Python::with_gil(|py| {
+            let event = DecisionEvent {
+                ctx: RequestContext {
+                    inner: event.ctx.clone(),
+                },
+                outcome: Outcome {
+                    inner: event.outcome.clone(),
+                },
+                auth_decision: event.auth_decision.map(|d| format!("{:?}", d)),
+                refresh_result: event.refresh_result,
+                decision: Decision {
+                    inner: event.decision.clone(),
+                    timeout_ms: event.timeout_ms,
+                },
+            };
+
+            // There is no caller to raise into: report it like an
+            // exception in `__del__`.
+            if let Err(err) = self.0.call1(py, (event,)) {
+                err.write_unraisable_bound(py, Some(self.0.bind(py)));
+            }
+        });
+    }
+}
+
+#[pyclass(module = "transport_core._native")]
+pub struct Client {
+    inner: CoreClient,
+}
+
+#[pymethods]
+impl Client {
+    /// `policy_json` is a JSON policy document; missing fields keep
+    /// their defaults.
+    #[new]
+    #[pyo3(signature = (policy_json = None))]
+    fn new(policy_json: Option<&str>) -> PyResult<Self> {
+        let policy = match policy_json {
+            Some(json) => parse_policy(json)?,
+            None => Policy::default(),
+        };
+
+        Ok(Self {
+            inner: CoreClient::with_policy(policy),
+        })
+    }
+
+    /// Replaces the policy; concurrency limit adaptation restarts.
+    fn set_policy(&mut self, policy_json: &str) -> PyResult<()> {
+        let policy = parse_policy(policy_json)?;
+        self.inner
+            .set_concurrency_policy(policy.concurrency.clone());
+        *self.inner.policy_mut() = policy;
+        Ok(())
+    }
+
+    /// The policy as a JSON document.
+    fn policy_json(&self) -> String {
+        serde_json::to_string(self.inner.policy()).expect("policy serializes to JSON")
+    }
+
+    /// Decides with the GIL released.
+    ///
+    /// `auth_decision` is `"RefreshAndRetry"`, `"Fail"` or `None`.
+    #[pyo3(signature = (ctx, outcome, auth_decision = None, refresh_result = None))]
+    fn decide(
+        &mut self,
+        py: Python<'_>,
+        ctx: &RequestContext,
+        outcome: &Outcome,
+        auth_decision: Option<&str>,
+        refresh_result: Option<bool>,
+    ) -> PyResult<Decision> {
+        let auth_decision = parse_auth_decision(auth_decision)?;
+
+        let ctx = ctx.inner.clone();
+        let outcome = outcome.inner.clone();
+        let client = &mut self.inner;
+
+        let (decision, timeout_ms) = py.allow_threads(move || {
+            let decision = client.decide(&ctx, outcome, auth_decision, refresh_result);
+            (decision, client.last_timeout_ms())
+        });
+
+        Ok(Decision {
+            inner: decision,
+            timeout_ms,
+        })
+    }
+
+    /// JSON-in/JSON-out entry point, see `json::decide_json`.
+    fn decide_json(&mut self, py: Python<'_>, input: &str) -> String {
+        let client = &mut self.inner;
+        py.allow_threads(move || client.decide_json(input))
+    }
+
+    /// Reports a finished attempt; `outcome` is `None` for a success.
+    #[pyo3(signature = (latency_ms, outcome = None, partition = None))]
+    fn record_attempt(
+        &mut self,
+        latency_ms: u32,
+        outcome: Option<&Outcome>,
+        partition: Option<&str>,
+    ) {
+        self.inner
+            .record_attempt(partition, latency_ms, outcome.map(|o| &o.inner));
+    }
+
+    #[pyo3(signature = (partition = None))]
+    fn recommended_timeout_ms(&self, partition: Option<&str>) -> u32 {
+        self.inner.recommended_timeout_ms(partition)
+    }
+
+    /// Asks for an in-flight slot: `"Acquired"`, `"Queued"` or
+    /// `"Rejected"`. Pass `queued=True` when retrying a queued request.
+    #[pyo3(signature = (queued = false, priority = "Normal"))]
+    fn acquire(&mut self, queued: bool, priority: &str) -> PyResult<String> {
+        let admission = self.inner.acquire(queued, parse_priority(priority)?);
+        Ok(format!("{:?}", admission))
+    }
+
+    fn cancel_queued(&mut self) {
+        self.inner.cancel_queued();
+    }
+
+    /// Releases a slot from `acquire`; `outcome` is `None` for a success.
+    #[pyo3(signature = (latency_ms, outcome = None))]
+    fn release(&mut self, latency_ms: u32, outcome: Option<&Outcome>) {
+        self.inner.release(latency_ms, outcome.map(|o| &o.inner));
+    }
+
+    fn concurrency_limit(&self) -> u32 {
+        self.inner.concurrency().limit()
+    }
+
+    /// Delay after which to send another hedge, or `None`.
+    #[pyo3(signature = (ctx, hedges_sent = 0))]
+    fn plan_hedge(&mut self, ctx: &RequestContext, hedges_sent: u8) -> Option<u32> {
+        self.inner.plan_hedge(&ctx.inner, hedges_sent)
+    }
+
+    fn report_hedge_result(&mut self, hedges_sent: u8) {
+        self.inner.report_hedge_result(hedges_sent);
+    }
+
+    /// Forgets all learned state; the policy is kept.
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Clears the auth state so a later 401 may refresh again.
+    fn reset_auth(&mut self) {
+        self.inner.reset_auth();
+    }
+
+    /// Point-in-time view of the client's state, as a dict.
+    fn state(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let snapshot =
+            serde_json::to_string(&self.inner.snapshot()).expect("snapshot serializes to JSON");
+        json_to_py(py, &snapshot)
+    }
+
+    /// Learned state as a versioned JSON document; `now_ms` is the
+    /// host's clock.
+    fn export_state(&self, now_ms: u64) -> String {
+        self.inner.export_state(now_ms).to_json()
+    }
+
+    /// Replaces the learned state with a document from `export_state`.
+    fn import_state(&mut self, state: &str) -> PyResult<()> {
+        ExportedState::from_json(state)
+            .and_then(|state| self.inner.import_state(state))
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Records the rules each `decide` evaluates (off by default).
+    fn set_explain(&mut self, explain: bool) {
+        self.inner.set_explain(explain);
+    }
+
+    /// Rules evaluated by the last `decide`, or `None` if explanations
+    /// are off.
+    fn last_explanation(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.inner
+            .last_explanation()
+            .map(|explanation| json_to_py(py, &explanation.to_json()))
+            .transpose()
+    }
+
+    /// Calls `observer(event)` with a `DecisionEvent` after every
+    /// decision; `None` removes it. Exceptions raised by the observer
+    /// are reported as unraisable rather than propagated.
+    #[pyo3(signature = (observer))]
+    fn set_observer(&mut self, observer: Option<PyObject>) {
+        match observer {
+            Some(observer) => self.inner.set_observer(Arc::new(PyObserver(observer))),
+            None => self.inner.clear_observer(),
+        }
+    }
+
+    /// Decision counters and histograms, as
+    /// `{"counters": [...], "histograms": [...]}`.
+    fn metrics(&self, py: Python<'_>) -> PyResult<PyObject> {
+        json_to_py(py, &self.inner.metrics_snapshot().to_json())
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    fn metrics_prometheus(&self) -> String {
+        self.inner.metrics_snapshot().to_prometheus()
+    }
+
+    fn set_recording(&mut self, recording: bool) {
+        self.inner.set_recording(recording);
+    }
+
+    /// Drains the decisions recorded since the last call as JSON lines.
+    fn take_recording(&mut self) -> String {
+        self.inner.take_recording()
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "_native")]
+fn native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Client>()?;
+    m.add_class::<RequestContext>()?;
+    m.add_class::<Outcome>()?;
+    m.add_class::<Decision>()?;
+    m.add_class::<DecisionEvent>()?;
+    Ok(())
+}
@@ -0,0 +1,278 @@
+//! Recording of `decide` calls and replay against the current engine.
+//!
+//! A recording [`Client`](crate::client::Client) writes one JSON line per
+//! [`Entry`]: a `start` line with the policy and auth state it began
+//! with, a `decide` line per call with its inputs and the decision made,
+//! and `policy` / `auth` lines whenever either changed other than through
+//! `decide`. [`replay`] feeds the same inputs through this build's engine
+//! and lists every decision that came out differently, so a log captured
+//! with one version shows what an upgrade (or a policy change) would do.
+//!
+//! Only what `decide` depends on is recorded. Whether the client was
+//! overloaded is recorded per call rather than re-derived, since the
+//! concurrency slots behind it are host-driven.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{AuthDecision, AuthState},
+    decision::{decide_with_policy, shed_retry},
+    model::{Decision, Outcome, RequestContext},
+    policy::Policy,
+};
+
+/// Version of the recording format, written on every `start` line.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// One line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Entry {
+    /// Recording began; replay restarts from this state.
+    Start {
+        version: u32,
+        engine_version: String,
+        policy: Policy,
+        auth: AuthState,
+    },
+    Decide(RecordedDecision),
+    /// The policy was changed between decisions.
+    Policy {
+        policy: Policy,
+    },
+    /// The auth state was reset or imported between decisions.
+    Auth {
+        auth: AuthState,
+    },
+}
+
+/// The inputs and result of one `decide` call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedDecision {
+    pub ctx: RequestContext,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_decision: Option<AuthDecision>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_result: Option<bool>,
+    /// Whether the client was over its concurrency limit, which sheds
+    /// non-critical retries.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub overloaded: bool,
+    pub decision: Decision,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingError {
+    /// A line is not a valid [`Entry`]; `line` is 1-based.
+    Malformed { line: usize, message: String },
+    /// A `start` line was written by an incompatible version.
+    UnsupportedVersion { line: usize, version: u32 },
+    /// The log does not begin with a `start` line.
+    MissingStart,
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::Malformed { line, message } => {
+                write!(f, "line {line}: malformed entry: {message}")
+            }
+            RecordingError::UnsupportedVersion { line, version } => write!(
+                f,
+                "line {line}: unsupported recording version {version} (expected {RECORDING_VERSION})"
+            ),
+            RecordingError::MissingStart => f.write_str("recording does not begin with a start line"),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl Entry {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("entry serializes to JSON")
+    }
+}
+
+/// Parses a recording, skipping blank lines. Logs taken in several
+/// chunks may simply be concatenated.
+pub fn parse(log: &str) -> Result<Vec<Entry>, RecordingError> {
+    let mut entries = Vec::new();
+
+    for (index, raw) in log.lines().enumerate() {
+        let line = index + 1;
+        if raw.trim().is_empty() {
+            continue;
+        }
+
+        let entry: Entry = serde_json::from_str(raw).map_err(|err| RecordingError::Malformed {
+            line,
+            message: err.to_string(),
+        })?;
+        match &entry {
+            Entry::Start { version, .. } if *version != RECORDING_VERSION => {
+                return Err(RecordingError::UnsupportedVersion {
+                    line,
+                    version: *version,
+                })
+            }
+            Entry::Start { .. } => {}
+            _ if entries.is_empty() => return Err(RecordingError::MissingStart),
+            _ => {}
+        }
+        entries.push(entry);
+    }
+
+    if entries.is_empty() {
+        return Err(RecordingError::MissingStart);
+    }
+    Ok(entries)
+}
+
+/// A decision the current engine makes differently.
+#[derive(Debug, Clone, Serialize)]
+pub struct Difference {
+    /// 0-based index of the `decide` entry among the parsed entries.
+    pub entry: usize,
+    pub ctx: RequestContext,
+    pub outcome: Outcome,
+    pub recorded: Decision,
+    pub replayed: Decision,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Engine versions that wrote the recording, in order of appearance.
+    pub engine_versions: Vec<String>,
+    pub decisions: usize,
+    pub differences: Vec<Difference>,
+}
+
+impl ReplayReport {
+    pub fn is_identical(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+}
+
+/// Replays `entries` through the current engine. With `policy`, every
+/// decision is made under it instead of the recorded policies, which
+/// shows what a policy change would have done to the recorded traffic.
+pub fn replay(entries: &[Entry], policy: Option<&Policy>) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let mut current_policy = Policy::default();
+    let mut auth = AuthState::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        match entry {
+            Entry::Start {
+                engine_version,
+                policy: recorded,
+                auth: recorded_auth,
+                ..
+            } => {
+                if report.engine_versions.last() != Some(engine_version) {
+                    report.engine_versions.push(engine_version.clone());
+                }
+                current_policy = recorded.clone();
+                auth = recorded_auth.clone();
+            }
+            Entry::Policy { policy: recorded } => current_policy = recorded.clone(),
+            Entry::Auth { auth: recorded } => auth = recorded.clone(),
+            Entry::Decide(recorded) => {
+                report.decisions += 1;
+
+                let decision = decide_with_policy(
+                    &recorded.ctx,
+                    recorded.outcome.clone(),
+                    recorded.auth_decision,
+                    &mut auth,
+                    recorded.refresh_result,
+                    policy.unwrap_or(&current_policy),
+                );
+                let decision = shed_retry(&recorded.ctx, decision, recorded.overloaded);
+
+                if decision != recorded.decision {
+                    report.differences.push(Difference {
+                        entry: index,
+                        ctx: recorded.ctx.clone(),
+                        outcome: recorded.outcome.clone(),
+                        recorded: recorded.decision.clone(),
+                        replayed: decision,
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Buffers entries for a recording client until the host takes them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Recorder {
+    /// The policy in effect as of the last entry, while recording.
+    policy: Option<Policy>,
+    buffer: String,
+}
+
+impl Recorder {
+    pub(crate) fn is_on(&self) -> bool {
+        self.policy.is_some()
+    }
+
+    pub(crate) fn start(&mut self, policy: &Policy, auth: &AuthState) {
+        self.policy = Some(policy.clone());
+        self.push(&Entry::Start {
+            version: RECORDING_VERSION,
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            policy: policy.clone(),
+            auth: auth.clone(),
+        });
+    }
+
+    /// Stops recording; entries not yet taken are kept.
+    pub(crate) fn stop(&mut self) {
+        self.policy = None;
+    }
+
+    pub(crate) fn record_decision(&mut self, policy: &Policy, decision: RecordedDecision) {
+        let Some(recorded) = &self.policy else {
+            return;
+        };
+        // `Client::policy_mut` hands out the policy directly, so changes
+        // are noticed here rather than where they happen.
+        if recorded != policy {
+            self.policy = Some(policy.clone());
+            self.push(&Entry::Policy {
+                policy: policy.clone(),
+            });
+        }
+        self.push(&Entry::Decide(decision));
+    }
+
+    pub(crate) fn record_auth(&mut self, auth: &AuthState) {
+        if self.is_on() {
+            self.push(&Entry::Auth { auth: auth.clone() });
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> String {
+        std::mem::take(&mut self.buffer)
+    }
+
+    fn push(&mut self, entry: &Entry) {
+        self.buffer.push_str(&entry.to_json());
+        self.buffer.push('\n');
+    }
+}
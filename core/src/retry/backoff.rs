@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::{RequestContext, RetryReason};
 
 pub fn retry_delay_ms(_ctx: &RequestContext, reason: RetryReason) -> u32 {
@@ -13,3 +15,63 @@ const MAX_RETRY_AFTER_MS: u32 = 120_000;
 pub fn clamp_retry_after(ms: u32) -> u32 {
     ms.min(MAX_RETRY_AFTER_MS)
 }
+
+/// How a server-supplied `retry_after_ms` hint is combined with the
+/// computed backoff for `Outcome::RateLimited`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HintMode {
+    /// Use the hint as-is (after clamping). This is the v1 behavior.
+    #[default]
+    HintOnly,
+    /// Use whichever of the hint and the computed backoff is larger.
+    MaxOfHintAndBackoff,
+    /// Use the hint plus a deterministic jitter in `0..=max_jitter_ms`.
+    HintPlusJitter { max_jitter_ms: u32 },
+}
+
+/// Combines a retry-after hint with the computed backoff.
+///
+/// Jitter is derived from `seed` and the request context, so the same
+/// inputs always yield the same delay; clients spread out by using
+/// different seeds.
+pub fn combine_retry_after(
+    ctx: &RequestContext,
+    hint_ms: u32,
+    backoff_ms: u32,
+    mode: HintMode,
+    seed: u64,
+) -> u32 {
+    let hint_ms = clamp_retry_after(hint_ms);
+
+    let combined = match mode {
+        HintMode::HintOnly => hint_ms,
+        HintMode::MaxOfHintAndBackoff => hint_ms.max(backoff_ms),
+        HintMode::HintPlusJitter { max_jitter_ms } => {
+            hint_ms.saturating_add(jitter_ms(ctx, seed, max_jitter_ms))
+        }
+    };
+
+    clamp_retry_after(combined)
+}
+
+fn jitter_ms(ctx: &RequestContext, seed: u64, max_jitter_ms: u32) -> u32 {
+    if max_jitter_ms == 0 {
+        return 0;
+    }
+
+    let mut h = seed ^ u64::from(ctx.attempt);
+    if let Some(key) = &ctx.idempotency_key {
+        for b in key.bytes() {
+            h = splitmix64(h ^ u64::from(b));
+        }
+    }
+
+    (splitmix64(h) % (u64::from(max_jitter_ms) + 1)) as u32
+}
+
+pub(crate) fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
@@ -1,8 +1,23 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::{HttpMethod, RequestContext};
 
 mod backoff;
 
-pub use backoff::{clamp_retry_after, retry_delay_ms};
+pub(crate) use backoff::splitmix64;
+pub use backoff::{clamp_retry_after, combine_retry_after, retry_delay_ms, HintMode};
+
+/// Configurable retry behavior. The default reproduces the v1 decisions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub hint_mode: HintMode,
+    /// Fixed seed for jitter, for reproducible delays in tests and
+    /// replay. By default each [`Client`](crate::client::Client) draws
+    /// its own, so clients sharing a Retry-After hint do not return in
+    /// lockstep; the stateless entry points, having no client, use 0.
+    pub jitter_seed: Option<u64>,
+}
 
 pub fn is_idempotent(method: &HttpMethod) -> bool {
     matches!(
@@ -0,0 +1,431 @@
+//! Discrete-event load simulation for comparing policies offline.
+//!
+//! Virtual clients send requests to a modelled upstream on a virtual
+//! clock, driving a real [`Client`] the way a host would: every attempt
+//! asks for a concurrency slot first, waiting when queued and giving up
+//! when rejected, reports its latency and outcome when it finishes, and
+//! failed attempts go through [`Client::decide`]. Retries wait for an
+//! overloaded upstream as long as the engine's recommended timeout. The
+//! report shows how a policy behaves under load: how many attempts each
+//! request cost, how many requests succeeded and how long they took.
+//! Runs are deterministic for a given seed.
+//!
+//! Hedging is not simulated.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, VecDeque},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Client,
+    concurrency::Admission,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+    retry::splitmix64,
+};
+
+/// The modelled server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Upstream {
+    /// Requests served concurrently; attempts beyond it time out.
+    pub capacity: u32,
+    /// Chance, in percent, that a served attempt fails with a network
+    /// error.
+    pub failure_percent: u8,
+    pub latency_ms: u32,
+    /// Served attempts take `latency_ms` plus up to this much more.
+    pub latency_jitter_ms: u32,
+    /// Attempts accepted per second before answering rate limited.
+    pub rate_limit_per_sec: Option<u32>,
+    /// Whether rate limited answers carry a Retry-After hint (the time
+    /// left in the current second).
+    pub send_retry_after: bool,
+}
+
+impl Default for Upstream {
+    fn default() -> Self {
+        Self {
+            capacity: 100,
+            failure_percent: 0,
+            latency_ms: 50,
+            latency_jitter_ms: 0,
+            rate_limit_per_sec: None,
+            send_retry_after: true,
+        }
+    }
+}
+
+/// One simulation run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub policy: Policy,
+    pub upstream: Upstream,
+    pub clients: u32,
+    pub requests_per_client: u32,
+    /// Each client starts a request this often, whether or not earlier
+    /// ones have finished.
+    pub request_interval_ms: u32,
+    pub max_attempts: u8,
+    pub method: HttpMethod,
+    pub priority: Priority,
+    /// How long clients wait for an overloaded upstream on a first
+    /// attempt; retries use the timeout the engine recommends.
+    pub client_timeout_ms: u32,
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            policy: Policy::default(),
+            upstream: Upstream::default(),
+            clients: 10,
+            requests_per_client: 100,
+            request_interval_ms: 100,
+            max_attempts: 3,
+            method: HttpMethod::GET,
+            priority: Priority::Normal,
+            client_timeout_ms: 1_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Percentiles over request latencies, in virtual milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+impl LatencySummary {
+    fn from_samples(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+
+        let percentile = |p: usize| samples[(samples.len() - 1) * p / 100];
+        Self {
+            count: samples.len(),
+            mean_ms: samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: samples[samples.len() - 1],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub requests: u64,
+    pub attempts: u64,
+    /// Attempts per request; 1.0 means no retries.
+    pub amplification: f64,
+    pub succeeded: u64,
+    pub success_rate: f64,
+    /// Failed requests by `FailReason`, plus `Rejected` for requests
+    /// refused a concurrency slot.
+    pub failures: BTreeMap<String, u64>,
+    /// Attempts that had to wait for a concurrency slot.
+    pub queued: u64,
+    /// Attempts by the outcome the upstream answered with.
+    pub outcomes: BTreeMap<String, u64>,
+    /// From the first attempt until success.
+    pub success_latency: LatencySummary,
+    /// From the first attempt until the final decision, for every
+    /// request.
+    pub latency: LatencySummary,
+    /// Virtual time until the last request finished.
+    pub duration_ms: u64,
+}
+
+impl SimulationReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EventKind {
+    /// The attempt's answer arrives; `served` attempts held capacity.
+    AttemptEnd {
+        request: usize,
+        served: bool,
+    },
+    AttemptStart {
+        request: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Event {
+    at_ms: u64,
+    /// Insertion order, so simultaneous events run first-in first-out.
+    seq: u64,
+    kind: EventKind,
+}
+
+struct Request {
+    client: usize,
+    started_ms: u64,
+    attempt: u8,
+    /// Whether the request holds a queue slot from `Admission::Queued`.
+    queued: bool,
+    attempt_started_ms: u64,
+    /// How long the current attempt waits for an overloaded upstream.
+    timeout_ms: u64,
+    outcome: Option<Outcome>,
+}
+
+struct Simulation<'a> {
+    config: &'a SimulationConfig,
+    clients: Vec<Client>,
+    /// Per client, requests waiting for a slot, oldest first.
+    waiting: Vec<VecDeque<usize>>,
+    requests: Vec<Request>,
+    queue: BinaryHeap<Reverse<Event>>,
+    seq: u64,
+    rng: u64,
+    in_flight: u32,
+    window_start_ms: u64,
+    window_count: u32,
+    report: SimulationReport,
+    latencies: Vec<u64>,
+    success_latencies: Vec<u64>,
+}
+
+/// Runs the simulation to completion.
+pub fn simulate(config: &SimulationConfig) -> SimulationReport {
+    let mut sim = Simulation {
+        config,
+        clients: (0..config.clients)
+            .map(|i| {
+                let mut policy = config.policy.clone();
+                // Fixed but distinct seeds keep runs reproducible.
+                let seed = policy.retry.jitter_seed.unwrap_or(0);
+                policy.retry.jitter_seed = Some(seed.wrapping_add(u64::from(i)));
+                Client::with_policy(policy)
+            })
+            .collect(),
+        waiting: vec![VecDeque::new(); config.clients as usize],
+        requests: Vec::new(),
+        queue: BinaryHeap::new(),
+        seq: 0,
+        rng: config.seed,
+        in_flight: 0,
+        window_start_ms: 0,
+        window_count: 0,
+        report: SimulationReport::default(),
+        latencies: Vec::new(),
+        success_latencies: Vec::new(),
+    };
+
+    // Spread client start times over one interval.
+    let clients = u64::from(config.clients.max(1));
+    let interval = u64::from(config.request_interval_ms);
+    for n in 0..config.requests_per_client {
+        for client in 0..config.clients as usize {
+            let at_ms = u64::from(n) * interval + client as u64 * interval / clients;
+            sim.requests.push(Request {
+                client,
+                started_ms: at_ms,
+                attempt: 1,
+                queued: false,
+                attempt_started_ms: at_ms,
+                timeout_ms: u64::from(config.client_timeout_ms),
+                outcome: None,
+            });
+            let request = sim.requests.len() - 1;
+            sim.schedule(at_ms, EventKind::AttemptStart { request });
+        }
+    }
+
+    while let Some(Reverse(event)) = sim.queue.pop() {
+        sim.report.duration_ms = event.at_ms;
+        match event.kind {
+            EventKind::AttemptStart { request } => sim.start_attempt(event.at_ms, request),
+            EventKind::AttemptEnd { request, served } => {
+                sim.end_attempt(event.at_ms, request, served)
+            }
+        }
+    }
+
+    sim.finish()
+}
+
+impl Simulation<'_> {
+    fn schedule(&mut self, at_ms: u64, kind: EventKind) {
+        self.seq += 1;
+        self.queue.push(Reverse(Event {
+            at_ms,
+            seq: self.seq,
+            kind,
+        }));
+    }
+
+    fn random(&mut self, below: u64) -> u64 {
+        self.rng = splitmix64(self.rng);
+        if below == 0 {
+            0
+        } else {
+            self.rng % below
+        }
+    }
+
+    fn context(&self, request: usize) -> RequestContext {
+        RequestContext {
+            method: self.config.method.clone(),
+            attempt: self.requests[request].attempt,
+            max_attempts: self.config.max_attempts,
+            idempotency_key: None,
+            allow_non_idempotent_retry: false,
+            partition: None,
+            priority: self.config.priority,
+        }
+    }
+
+    fn start_attempt(&mut self, now_ms: u64, request: usize) {
+        let req = &mut self.requests[request];
+        let client = req.client;
+        match self.clients[client].acquire(req.queued, self.config.priority) {
+            Admission::Acquired => req.queued = false,
+            Admission::Queued => {
+                if !req.queued {
+                    req.queued = true;
+                    self.report.queued += 1;
+                    self.waiting[client].push_back(request);
+                } else {
+                    // Still no slot: keep its place at the front.
+                    self.waiting[client].push_front(request);
+                }
+                return;
+            }
+            Admission::Rejected => {
+                self.finish_request(now_ms, request, Some("Rejected".to_string()));
+                return;
+            }
+        }
+        req.attempt_started_ms = now_ms;
+
+        let upstream = &self.config.upstream;
+        self.report.attempts += 1;
+
+        if now_ms >= self.window_start_ms + 1_000 {
+            self.window_start_ms = now_ms - (now_ms - self.window_start_ms) % 1_000;
+            self.window_count = 0;
+        }
+        self.window_count += 1;
+
+        let (outcome, after_ms, served) = match upstream.rate_limit_per_sec {
+            Some(limit) if self.window_count > limit => {
+                let left_ms = (self.window_start_ms + 1_000 - now_ms) as u32;
+                let retry_after_ms = upstream.send_retry_after.then_some(left_ms);
+                (Some(Outcome::RateLimited { retry_after_ms }), 1, false)
+            }
+            _ if self.in_flight >= upstream.capacity => (
+                Some(Outcome::TimeoutError),
+                self.requests[request].timeout_ms,
+                false,
+            ),
+            _ => {
+                let jitter = self.random(u64::from(upstream.latency_jitter_ms) + 1);
+                let failed = self.random(100) < u64::from(upstream.failure_percent);
+                self.in_flight += 1;
+                (
+                    failed.then_some(Outcome::NetworkError),
+                    u64::from(upstream.latency_ms) + jitter,
+                    true,
+                )
+            }
+        };
+
+        self.requests[request].outcome = outcome;
+        self.schedule(now_ms + after_ms, EventKind::AttemptEnd { request, served });
+    }
+
+    fn end_attempt(&mut self, now_ms: u64, request: usize, served: bool) {
+        if served {
+            self.in_flight -= 1;
+        }
+
+        let req = &self.requests[request];
+        let client = req.client;
+        let latency_ms = u32::try_from(now_ms - req.attempt_started_ms).unwrap_or(u32::MAX);
+        self.clients[client].record_attempt(None, latency_ms, req.outcome.as_ref());
+        self.clients[client].release(latency_ms, req.outcome.as_ref());
+        // The freed slot goes to the oldest waiting request.
+        if let Some(next) = self.waiting[client].pop_front() {
+            self.schedule(now_ms, EventKind::AttemptStart { request: next });
+        }
+
+        let ctx = self.context(request);
+        let req = &mut self.requests[request];
+        let label = match &req.outcome {
+            None => "Success".to_string(),
+            Some(Outcome::RateLimited { .. }) => "RateLimited".to_string(),
+            Some(outcome) => format!("{outcome:?}"),
+        };
+        *self.report.outcomes.entry(label).or_insert(0) += 1;
+
+        let Some(outcome) = req.outcome.take() else {
+            self.finish_request(now_ms, request, None);
+            return;
+        };
+
+        let client = &mut self.clients[client];
+        match client.decide(&ctx, outcome, None, None) {
+            Decision::Retry { after_ms, .. } | Decision::RefreshAndRetry { after_ms } => {
+                req.attempt = req.attempt.saturating_add(1);
+                req.timeout_ms = u64::from(
+                    client
+                        .last_timeout_ms()
+                        .unwrap_or(self.config.client_timeout_ms),
+                );
+                self.schedule(
+                    now_ms + u64::from(after_ms),
+                    EventKind::AttemptStart { request },
+                );
+            }
+            Decision::Fail { reason, .. } => {
+                self.finish_request(now_ms, request, Some(format!("{reason:?}")))
+            }
+            Decision::Proceed => self.finish_request(now_ms, request, None),
+        }
+    }
+
+    fn finish_request(&mut self, now_ms: u64, request: usize, failure: Option<String>) {
+        let latency = now_ms - self.requests[request].started_ms;
+        self.latencies.push(latency);
+
+        match failure {
+            None => {
+                self.report.succeeded += 1;
+                self.success_latencies.push(latency);
+            }
+            Some(reason) => *self.report.failures.entry(reason).or_insert(0) += 1,
+        }
+    }
+
+    fn finish(mut self) -> SimulationReport {
+        let requests = self.requests.len() as u64;
+        self.report.requests = requests;
+        if requests > 0 {
+            self.report.amplification = self.report.attempts as f64 / requests as f64;
+            self.report.success_rate = self.report.succeeded as f64 / requests as f64;
+        }
+        self.report.latency = LatencySummary::from_samples(self.latencies);
+        self.report.success_latency = LatencySummary::from_samples(self.success_latencies);
+        self.report
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Outcome;
+
+/// How per-attempt timeouts are derived from observed latencies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutPolicy {
+    /// Latency percentile the recommendation is based on (1..=100).
+    pub percentile: u8,
+    /// Multiplier applied to that percentile, in percent.
+    pub factor_percent: u32,
+    pub min_ms: u32,
+    pub max_ms: u32,
+    /// Recommendation used until `min_samples` attempts were reported.
+    pub default_ms: u32,
+    pub min_samples: usize,
+    /// Number of most recent samples kept per partition.
+    pub window: usize,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            percentile: 99,
+            factor_percent: 150,
+            min_ms: 100,
+            max_ms: 30_000,
+            default_ms: 10_000,
+            min_samples: 20,
+            window: 100,
+        }
+    }
+}
+
+/// Host-reported attempt results for one partition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionStats {
+    pub attempts: u64,
+    pub timeouts: u64,
+    pub failures: u64,
+    samples: VecDeque<u32>,
+}
+
+/// Attempt latencies and outcomes, keyed by `RequestContext::partition`.
+///
+/// Requests without a partition share the default partition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    default: PartitionStats,
+    partitions: BTreeMap<String, PartitionStats>,
+}
+
+impl PartitionStats {
+    pub fn samples(&self) -> impl Iterator<Item = u32> + '_ {
+        self.samples.iter().copied()
+    }
+
+    fn record(&mut self, latency_ms: u32, outcome: Option<&Outcome>, window: usize) {
+        self.attempts += 1;
+
+        match outcome {
+            None => {}
+            Some(Outcome::TimeoutError) => self.timeouts += 1,
+            Some(_) => self.failures += 1,
+        }
+
+        // A timed-out attempt took at least `latency_ms`; keeping it as a
+        // sample lets the recommendation relax when upstream slows down.
+        self.samples.push_back(latency_ms);
+        self.trim(window);
+    }
+
+    /// Drops the oldest samples beyond `window`.
+    fn trim(&mut self, window: usize) {
+        while self.samples.len() > window {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Latency percentile of the kept samples.
+    pub fn percentile_ms(&self, percentile: u8) -> Option<u32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<u32> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+
+        // Nearest-rank percentile: the ceil(p/100 * n)-th smallest sample.
+        let p = usize::from(percentile.clamp(1, 100));
+        let index = (p * sorted.len() - 1) / 100;
+        Some(sorted[index])
+    }
+}
+
+impl LatencyStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one finished attempt. `outcome` is `None` for a success.
+    pub fn record(
+        &mut self,
+        partition: Option<&str>,
+        latency_ms: u32,
+        outcome: Option<&Outcome>,
+        policy: &TimeoutPolicy,
+    ) {
+        let stats = match partition {
+            None => &mut self.default,
+            Some(name) => self.partitions.entry(name.to_string()).or_default(),
+        };
+
+        stats.record(latency_ms, outcome, policy.window.max(1));
+    }
+
+    /// Drops samples beyond `policy.window` in every partition, e.g.
+    /// after importing ones kept under another policy.
+    pub(crate) fn trim_to(&mut self, policy: &TimeoutPolicy) {
+        let window = policy.window.max(1);
+        self.default.trim(window);
+        for stats in self.partitions.values_mut() {
+            stats.trim(window);
+        }
+    }
+
+    pub fn partition(&self, partition: Option<&str>) -> Option<&PartitionStats> {
+        match partition {
+            None => Some(&self.default),
+            Some(name) => self.partitions.get(name),
+        }
+    }
+
+    /// Number of named partitions with recorded attempts.
+    pub fn partition_count(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// Recommended per-attempt timeout for the partition.
+    pub fn recommended_timeout_ms(&self, partition: Option<&str>, policy: &TimeoutPolicy) -> u32 {
+        let stats = match self.partition(partition) {
+            Some(stats) if stats.samples.len() >= policy.min_samples.max(1) => stats,
+            _ => return policy.default_ms,
+        };
+
+        let p = match stats.percentile_ms(policy.percentile) {
+            Some(p) => p,
+            None => return policy.default_ms,
+        };
+
+        let scaled = u64::from(p) * u64::from(policy.factor_percent) / 100;
+        let scaled = u32::try_from(scaled).unwrap_or(u32::MAX);
+
+        scaled.clamp(policy.min_ms, policy.max_ms.max(policy.min_ms))
+    }
+}
@@ -0,0 +1,151 @@
+//! JavaScript interface for `wasm32-unknown-unknown`.
+//!
+//! Inputs and outputs are plain JS objects using the same field names
+//! as the serde representation of the `model` types, e.g.
+//!
+//! ```js
+//! const client = new Client();
+//! const decision = client.decide(
+//!   { method: "GET", attempt: 1, max_attempts: 3,
+//!     idempotency_key: null, allow_non_idempotent_retry: false },
+//!   { RateLimited: { retry_after_ms: 3000 } },
+//! );
+//! // { action: "RETRY", after_ms: 3000, retry_reason: "RateLimited", ... }
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    auth::AuthDecision,
+    client::Client,
+    model::{Decision, FailReason, Outcome, RequestContext, RetryReason},
+    policy::Policy,
+};
+
+/// A decision flattened into one object, so JS callers can branch on
+/// `action` without unwrapping enum variants.
+#[derive(Debug, Serialize)]
+struct DecisionView {
+    action: &'static str,
+    after_ms: Option<u32>,
+    retry_reason: Option<RetryReason>,
+    fail_reason: Option<FailReason>,
+    retryable: bool,
+    timeout_ms: Option<u32>,
+}
+
+impl DecisionView {
+    fn new(decision: &Decision, timeout_ms: Option<u32>) -> Self {
+        let mut view = DecisionView {
+            action: "PROCEED",
+            after_ms: None,
+            retry_reason: None,
+            fail_reason: None,
+            retryable: false,
+            timeout_ms,
+        };
+
+        match decision {
+            Decision::Proceed => {}
+            Decision::Retry { after_ms, reason } => {
+                view.action = "RETRY";
+                view.after_ms = Some(*after_ms);
+                view.retry_reason = Some(*reason);
+            }
+            Decision::RefreshAndRetry { after_ms } => {
+                view.action = "REFRESH_AND_RETRY";
+                view.after_ms = Some(*after_ms);
+            }
+            Decision::Fail { reason, retryable } => {
+                view.action = "FAIL";
+                view.fail_reason = Some(*reason);
+                view.retryable = *retryable;
+            }
+        }
+
+        view
+    }
+}
+
+#[wasm_bindgen(js_name = Client)]
+pub struct WasmClient {
+    client: Client,
+}
+
+#[wasm_bindgen(js_class = Client)]
+impl WasmClient {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmClient {
+        WasmClient {
+            client: Client::new(),
+        }
+    }
+
+    /// Creates a client from a policy object (see `policy::Policy`).
+    /// Missing fields keep their defaults.
+    #[wasm_bindgen(js_name = withPolicy)]
+    pub fn with_policy(policy: JsValue) -> Result<WasmClient, JsError> {
+        let policy: Policy = serde_wasm_bindgen::from_value(policy)?;
+
+        Ok(WasmClient {
+            client: Client::with_policy(policy),
+        })
+    }
+
+    /// `auth_decision` is `"RefreshAndRetry"`, `"Fail"` or absent.
+    /// `refresh_result` is absent until the host attempted a refresh.
+    pub fn decide(
+        &mut self,
+        ctx: JsValue,
+        outcome: JsValue,
+        auth_decision: Option<String>,
+        refresh_result: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let ctx: RequestContext = serde_wasm_bindgen::from_value(ctx)?;
+        let outcome: Outcome = serde_wasm_bindgen::from_value(outcome)?;
+
+        let auth_decision = match auth_decision.as_deref() {
+            None | Some("Fail") => None,
+            Some("RefreshAndRetry") => Some(AuthDecision::RefreshAndRetry),
+            Some(other) => {
+                return Err(JsError::new(&format!("unknown auth decision: {}", other)));
+            }
+        };
+
+        let decision = self
+            .client
+            .decide(&ctx, outcome, auth_decision, refresh_result);
+        let view = DecisionView::new(&decision, self.client.last_timeout_ms());
+
+        // JSON-compatible output: `null` instead of `undefined` for
+        // absent fields.
+        Ok(view.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+    }
+
+    /// Reports a finished attempt; `outcome` is `null` for a success.
+    #[wasm_bindgen(js_name = recordAttempt)]
+    pub fn record_attempt(
+        &mut self,
+        partition: Option<String>,
+        latency_ms: u32,
+        outcome: JsValue,
+    ) -> Result<(), JsError> {
+        let outcome: Option<Outcome> = serde_wasm_bindgen::from_value(outcome)?;
+
+        self.client
+            .record_attempt(partition.as_deref(), latency_ms, outcome.as_ref());
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = recommendedTimeoutMs)]
+    pub fn recommended_timeout_ms(&self, partition: Option<String>) -> u32 {
+        self.client.recommended_timeout_ms(partition.as_deref())
+    }
+}
+
+impl Default for WasmClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
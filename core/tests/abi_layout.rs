@@ -0,0 +1,331 @@
+//! Compiles a small C program against `ffi/transport_core.h` and checks
+//! that sizes, field offsets and enum values agree with the Rust side.
+//!
+//! Skipped when no C compiler is available (`$CC`, falling back to `cc`).
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    mem::{size_of, MaybeUninit},
+    path::Path,
+    process::Command,
+};
+
+use transport_core::ffi::*;
+use transport_core::model::{FailReason, RetryReason};
+
+const PROGRAM: &str = r#"
+#include <stddef.h>
+#include <stdio.h>
+#include "transport_core.h"
+
+#define SIZE(t) printf("sizeof %s %zu\n", #t, sizeof(t))
+#define OFFSET(t, f) printf("offset %s.%s %zu\n", #t, #f, offsetof(t, f))
+#define VALUE(v) printf("value %s %lld\n", #v, (long long)(v))
+
+int main(void) {
+  VALUE(TRANSPORT_CORE_ABI_VERSION);
+
+  SIZE(tc_request_context_t);
+  OFFSET(tc_request_context_t, method);
+  OFFSET(tc_request_context_t, attempt);
+  OFFSET(tc_request_context_t, max_attempts);
+  OFFSET(tc_request_context_t, allow_non_idempotent_retry);
+  OFFSET(tc_request_context_t, idempotency_key);
+  OFFSET(tc_request_context_t, partition);
+  OFFSET(tc_request_context_t, priority);
+
+  SIZE(tc_outcome_t);
+  OFFSET(tc_outcome_t, kind);
+  OFFSET(tc_outcome_t, http_status);
+  OFFSET(tc_outcome_t, retry_after_ms);
+
+  SIZE(tc_timeout_policy_t);
+  OFFSET(tc_timeout_policy_t, percentile);
+  OFFSET(tc_timeout_policy_t, factor_percent);
+  OFFSET(tc_timeout_policy_t, window);
+
+  SIZE(tc_concurrency_policy_t);
+  OFFSET(tc_concurrency_policy_t, algorithm);
+  OFFSET(tc_concurrency_policy_t, decrease_percent);
+  OFFSET(tc_concurrency_policy_t, max_queue);
+  OFFSET(tc_concurrency_policy_t, background_share_percent);
+  OFFSET(tc_concurrency_policy_t, vegas_alpha);
+  OFFSET(tc_concurrency_policy_t, vegas_beta);
+
+  SIZE(tc_decision_event_t);
+  OFFSET(tc_decision_event_t, partition);
+  OFFSET(tc_decision_event_t, decision);
+  OFFSET(tc_decision_event_t, fail_retryable);
+  OFFSET(tc_decision_event_t, timeout_ms);
+
+  SIZE(tc_decision_t);
+  SIZE(tc_http_method_t);
+  SIZE(tc_priority_t);
+
+  VALUE(TC_DECISION_FAIL);
+  VALUE(TC_HTTP_OPTIONS);
+  VALUE(TC_OUTCOME_CAPTCHA);
+  VALUE(TC_PRIORITY_BACKGROUND);
+  VALUE(TC_ADMISSION_REJECTED);
+  VALUE(TC_HINT_PLUS_JITTER);
+  VALUE(TC_LIMIT_VEGAS);
+  VALUE(TC_RETRY_REASON_NETWORK);
+  VALUE(TC_RETRY_REASON_AUTH_EXPIRED);
+  VALUE(TC_FAIL_REASON_MAX_ATTEMPTS);
+  VALUE(TC_FAIL_REASON_SHED);
+  VALUE(TC_FAIL_REASON_UNKNOWN);
+  return 0;
+}
+"#;
+
+macro_rules! offset {
+    ($t:ty, $f:ident) => {{
+        let value = MaybeUninit::<$t>::uninit();
+        let base = value.as_ptr();
+        let field = unsafe { std::ptr::addr_of!((*base).$f) };
+        field as usize - base as usize
+    }};
+}
+
+/// Builds and runs the probe program, returning `name -> value`.
+fn probe() -> Option<BTreeMap<String, i64>> {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let source = out_dir.join("abi_layout.c");
+    let binary = out_dir.join("abi_layout");
+    fs::write(&source, PROGRAM).unwrap();
+
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg("-I")
+        .arg(crate_dir.join("../ffi"))
+        .arg(&source)
+        .arg("-o")
+        .arg(&binary)
+        .status();
+
+    match status {
+        Ok(s) if s.success() => {}
+        Ok(s) => panic!("{cc} failed to compile the header probe: {s}"),
+        Err(_) => {
+            eprintln!("skipping ABI layout check: `{cc}` not found");
+            return None;
+        }
+    }
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    Some(
+        stdout
+            .lines()
+            .map(|line| {
+                let mut parts = line.split_whitespace().skip(1);
+                let name = parts.next().unwrap().to_string();
+                let value = parts.next().unwrap().parse().unwrap();
+                (name, value)
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn header_layout_matches_rust() {
+    let Some(c) = probe() else { return };
+
+    let rust: Vec<(&str, i64)> = vec![
+        (
+            "TRANSPORT_CORE_ABI_VERSION",
+            TRANSPORT_CORE_ABI_VERSION as i64,
+        ),
+        (
+            "tc_request_context_t",
+            size_of::<tc_request_context_t>() as i64,
+        ),
+        (
+            "tc_request_context_t.method",
+            offset!(tc_request_context_t, method) as i64,
+        ),
+        (
+            "tc_request_context_t.attempt",
+            offset!(tc_request_context_t, attempt) as i64,
+        ),
+        (
+            "tc_request_context_t.max_attempts",
+            offset!(tc_request_context_t, max_attempts) as i64,
+        ),
+        (
+            "tc_request_context_t.allow_non_idempotent_retry",
+            offset!(tc_request_context_t, allow_non_idempotent_retry) as i64,
+        ),
+        (
+            "tc_request_context_t.idempotency_key",
+            offset!(tc_request_context_t, idempotency_key) as i64,
+        ),
+        (
+            "tc_request_context_t.partition",
+            offset!(tc_request_context_t, partition) as i64,
+        ),
+        (
+            "tc_request_context_t.priority",
+            offset!(tc_request_context_t, priority) as i64,
+        ),
+        ("tc_outcome_t", size_of::<tc_outcome_t>() as i64),
+        ("tc_outcome_t.kind", offset!(tc_outcome_t, kind) as i64),
+        (
+            "tc_outcome_t.http_status",
+            offset!(tc_outcome_t, http_status) as i64,
+        ),
+        (
+            "tc_outcome_t.retry_after_ms",
+            offset!(tc_outcome_t, retry_after_ms) as i64,
+        ),
+        (
+            "tc_timeout_policy_t",
+            size_of::<tc_timeout_policy_t>() as i64,
+        ),
+        (
+            "tc_timeout_policy_t.percentile",
+            offset!(tc_timeout_policy_t, percentile) as i64,
+        ),
+        (
+            "tc_timeout_policy_t.factor_percent",
+            offset!(tc_timeout_policy_t, factor_percent) as i64,
+        ),
+        (
+            "tc_timeout_policy_t.window",
+            offset!(tc_timeout_policy_t, window) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t",
+            size_of::<tc_concurrency_policy_t>() as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.algorithm",
+            offset!(tc_concurrency_policy_t, algorithm) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.decrease_percent",
+            offset!(tc_concurrency_policy_t, decrease_percent) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.max_queue",
+            offset!(tc_concurrency_policy_t, max_queue) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.background_share_percent",
+            offset!(tc_concurrency_policy_t, background_share_percent) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.vegas_alpha",
+            offset!(tc_concurrency_policy_t, vegas_alpha) as i64,
+        ),
+        (
+            "tc_concurrency_policy_t.vegas_beta",
+            offset!(tc_concurrency_policy_t, vegas_beta) as i64,
+        ),
+        (
+            "tc_decision_event_t",
+            size_of::<tc_decision_event_t>() as i64,
+        ),
+        (
+            "tc_decision_event_t.partition",
+            offset!(tc_decision_event_t, partition) as i64,
+        ),
+        (
+            "tc_decision_event_t.decision",
+            offset!(tc_decision_event_t, decision) as i64,
+        ),
+        (
+            "tc_decision_event_t.fail_retryable",
+            offset!(tc_decision_event_t, fail_retryable) as i64,
+        ),
+        (
+            "tc_decision_event_t.timeout_ms",
+            offset!(tc_decision_event_t, timeout_ms) as i64,
+        ),
+        ("tc_decision_t", size_of::<tc_decision_t>() as i64),
+        ("tc_http_method_t", size_of::<tc_http_method_t>() as i64),
+        ("tc_priority_t", size_of::<tc_priority_t>() as i64),
+        ("TC_DECISION_FAIL", tc_decision_t::TC_DECISION_FAIL as i64),
+        ("TC_HTTP_OPTIONS", tc_http_method_t::TC_HTTP_OPTIONS as i64),
+        (
+            "TC_OUTCOME_CAPTCHA",
+            tc_outcome_kind_t::TC_OUTCOME_CAPTCHA as i64,
+        ),
+        (
+            "TC_PRIORITY_BACKGROUND",
+            tc_priority_t::TC_PRIORITY_BACKGROUND as i64,
+        ),
+        (
+            "TC_ADMISSION_REJECTED",
+            tc_admission_t::TC_ADMISSION_REJECTED as i64,
+        ),
+        (
+            "TC_HINT_PLUS_JITTER",
+            tc_hint_mode_t::TC_HINT_PLUS_JITTER as i64,
+        ),
+        (
+            "TC_LIMIT_VEGAS",
+            tc_limit_algorithm_t::TC_LIMIT_VEGAS as i64,
+        ),
+        ("TC_RETRY_REASON_NETWORK", RetryReason::NetworkError as i64),
+        (
+            "TC_RETRY_REASON_AUTH_EXPIRED",
+            RetryReason::AuthExpired as i64,
+        ),
+        (
+            "TC_FAIL_REASON_MAX_ATTEMPTS",
+            FailReason::MaxAttemptsExceeded as i64,
+        ),
+        ("TC_FAIL_REASON_SHED", FailReason::Shed as i64),
+        ("TC_FAIL_REASON_UNKNOWN", FailReason::Unknown as i64),
+    ];
+
+    for (name, expected) in rust {
+        assert_eq!(c.get(name), Some(&expected), "{name}");
+    }
+}
+
+#[test]
+fn reason_enums_match_model() {
+    assert_eq!(
+        tc_retry_reason_t::TC_RETRY_REASON_NETWORK as u8,
+        RetryReason::NetworkError as u8
+    );
+    assert_eq!(
+        tc_retry_reason_t::TC_RETRY_REASON_TIMEOUT as u8,
+        RetryReason::Timeout as u8
+    );
+    assert_eq!(
+        tc_retry_reason_t::TC_RETRY_REASON_RATE_LIMITED as u8,
+        RetryReason::RateLimited as u8
+    );
+    assert_eq!(
+        tc_retry_reason_t::TC_RETRY_REASON_AUTH_EXPIRED as u8,
+        RetryReason::AuthExpired as u8
+    );
+
+    assert_eq!(
+        tc_fail_reason_t::TC_FAIL_REASON_MAX_ATTEMPTS as u8,
+        FailReason::MaxAttemptsExceeded as u8
+    );
+    assert_eq!(
+        tc_fail_reason_t::TC_FAIL_REASON_AUTH_FAILED as u8,
+        FailReason::AuthFailed as u8
+    );
+    assert_eq!(
+        tc_fail_reason_t::TC_FAIL_REASON_HARD_BLOCKED as u8,
+        FailReason::HardBlocked as u8
+    );
+    assert_eq!(
+        tc_fail_reason_t::TC_FAIL_REASON_SHED as u8,
+        FailReason::Shed as u8
+    );
+    assert_eq!(
+        tc_fail_reason_t::TC_FAIL_REASON_UNKNOWN as u8,
+        FailReason::Unknown as u8
+    );
+}
@@ -0,0 +1,103 @@
+use transport_core::{
+    client::Client,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+    stats::TimeoutPolicy,
+};
+
+fn client() -> Client {
+    Client::with_policy(Policy {
+        timeout: TimeoutPolicy {
+            percentile: 90,
+            factor_percent: 200,
+            min_ms: 50,
+            max_ms: 5_000,
+            default_ms: 1_000,
+            min_samples: 10,
+            window: 10,
+        },
+        ..Policy::default()
+    })
+}
+
+fn ctx(partition: Option<&str>) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: partition.map(str::to_string),
+        priority: Priority::Normal,
+    }
+}
+
+#[test]
+fn default_is_used_until_enough_samples() {
+    let mut client = client();
+
+    for _ in 0..9 {
+        client.record_attempt(None, 100, None);
+    }
+    assert_eq!(client.recommended_timeout_ms(None), 1_000);
+
+    client.record_attempt(None, 100, None);
+    assert_eq!(client.recommended_timeout_ms(None), 200);
+}
+
+#[test]
+fn recommendation_tracks_the_window_and_is_bounded() {
+    let mut client = client();
+
+    for ms in 1..=10 {
+        client.record_attempt(None, ms * 10, None);
+    }
+    // p90 of 10..=100 is 90, times 2.
+    assert_eq!(client.recommended_timeout_ms(None), 180);
+
+    // Upstream slows down: older samples fall out of the window.
+    for _ in 0..10 {
+        client.record_attempt(None, 4_000, Some(&Outcome::TimeoutError));
+    }
+    assert_eq!(client.recommended_timeout_ms(None), 5_000);
+
+    for _ in 0..10 {
+        client.record_attempt(None, 1, None);
+    }
+    assert_eq!(client.recommended_timeout_ms(None), 50);
+
+    let stats = client.latency_stats().partition(None).unwrap();
+    assert_eq!(stats.attempts, 30);
+    assert_eq!(stats.timeouts, 10);
+}
+
+#[test]
+fn partitions_are_tracked_independently() {
+    let mut client = client();
+
+    for _ in 0..10 {
+        client.record_attempt(Some("slow.example"), 2_000, None);
+        client.record_attempt(Some("fast.example"), 20, None);
+    }
+
+    assert_eq!(client.recommended_timeout_ms(Some("slow.example")), 4_000);
+    assert_eq!(client.recommended_timeout_ms(Some("fast.example")), 50);
+    assert_eq!(client.recommended_timeout_ms(None), 1_000);
+    assert_eq!(client.latency_stats().partition_count(), 2);
+}
+
+#[test]
+fn timeout_is_returned_alongside_retry_decisions() {
+    let mut client = client();
+
+    for _ in 0..10 {
+        client.record_attempt(Some("api"), 300, None);
+    }
+
+    let decision = client.decide(&ctx(Some("api")), Outcome::NetworkError, None, None);
+    assert!(matches!(decision, Decision::Retry { .. }));
+    assert_eq!(client.last_timeout_ms(), Some(600));
+
+    client.decide(&ctx(Some("api")), Outcome::Blocked, None, None);
+    assert_eq!(client.last_timeout_ms(), None);
+}
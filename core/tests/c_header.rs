@@ -0,0 +1,35 @@
+//! Keeps `ffi/transport_core.h` in sync with `src/ffi.rs`.
+//!
+//! Regenerate the header with `TC_BLESS_HEADER=1 cargo test --test c_header`.
+
+use std::{fs, path::Path};
+
+#[test]
+fn checked_in_header_matches_generated() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let header_path = crate_dir.join("../ffi/transport_core.h");
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate C header")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).expect("header is UTF-8");
+
+    if std::env::var_os("TC_BLESS_HEADER").is_some() {
+        fs::write(&header_path, &generated).expect("failed to write header");
+        return;
+    }
+
+    let checked_in = fs::read_to_string(&header_path).expect("failed to read header");
+
+    assert!(
+        checked_in == generated,
+        "ffi/transport_core.h is stale; run `TC_BLESS_HEADER=1 cargo test --test c_header`"
+    );
+}
@@ -0,0 +1,194 @@
+//! Run with `cargo test --features cli`.
+#![cfg(feature = "cli")]
+
+use std::io::Cursor;
+
+use transport_core::{
+    auth::AuthDecision,
+    cli::{events, main, simulate, Event, SimulateOptions},
+    client::Client,
+    model::{Decision, FailReason, HttpMethod, Outcome, Priority, RequestContext, RetryReason},
+};
+
+fn parse(line: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    events::parse_line(line, &mut events).unwrap();
+    events
+}
+
+fn ctx() -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority: Priority::Normal,
+    }
+}
+
+fn run(args: &[&str], stdin: &str) -> (i32, String) {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let mut stdout = Vec::new();
+    let code = main(&args, &mut Cursor::new(stdin), &mut stdout);
+    (code, String::from_utf8(stdout).unwrap())
+}
+
+#[test]
+fn dsl_tokens() {
+    let events = parse("401 refresh:ok 503 timeout 429:2000 http:404 200");
+
+    let outcomes: Vec<Option<Outcome>> = events.iter().map(|e| e.outcome.clone()).collect();
+    assert_eq!(
+        outcomes,
+        [
+            Some(Outcome::HttpStatus(401)),
+            Some(Outcome::NetworkError),
+            Some(Outcome::TimeoutError),
+            Some(Outcome::RateLimited {
+                retry_after_ms: Some(2000)
+            }),
+            Some(Outcome::HttpStatus(404)),
+            None,
+        ]
+    );
+    assert_eq!(events[0].auth_decision, Some(AuthDecision::RefreshAndRetry));
+    assert_eq!(events[0].refresh_result, Some(true));
+
+    let mut events = Vec::new();
+    assert!(events::parse_token("refresh:ok", &mut events).is_err());
+    assert!(events::parse_token("teapot", &mut events).is_err());
+}
+
+#[test]
+fn json_lines() {
+    let events = parse(r#"{"outcome": {"HttpStatus": 401}, "auth_decision": "Fail"}"#);
+    assert_eq!(events[0].auth_decision, Some(AuthDecision::Fail));
+    assert_eq!(parse("{}")[0].outcome, None);
+}
+
+#[test]
+fn requests_restart_after_a_final_decision() {
+    let rows = simulate(
+        &SimulateOptions {
+            latency_ms: 10,
+            ..SimulateOptions::default()
+        },
+        &parse("timeout timeout timeout 401 refresh:ok 200"),
+    );
+
+    let attempts: Vec<(u32, u8)> = rows.iter().map(|r| (r.request, r.attempt)).collect();
+    assert_eq!(attempts, [(1, 1), (1, 2), (1, 3), (2, 1), (2, 2)]);
+    assert_eq!(
+        rows[1].decision,
+        Some(Decision::Retry {
+            after_ms: 500,
+            reason: RetryReason::Timeout
+        })
+    );
+    assert_eq!(
+        rows[2].decision,
+        Some(Decision::Fail {
+            reason: FailReason::MaxAttemptsExceeded,
+            retryable: false
+        })
+    );
+    // The second request may refresh again.
+    assert_eq!(
+        rows[3].decision,
+        Some(Decision::RefreshAndRetry { after_ms: 0 })
+    );
+    assert_eq!(rows[4].decision, None);
+    assert_eq!(rows[4].elapsed_ms, 5 * 10 + 2 * 500);
+}
+
+#[test]
+fn prints_a_table_or_json_lines() {
+    let (code, table) = run(&["simulate", "503", "200"], "");
+    assert_eq!(code, 0);
+    assert!(table.contains("RETRY (NetworkError)"));
+    assert!(table.ends_with("1 request(s) finished, 1 succeeded, 2 attempt(s), 200 ms\n"));
+
+    let (code, json) = run(&["simulate", "--json"], "timeout\n{}\n");
+    assert_eq!(code, 0);
+    assert_eq!(json.lines().count(), 2);
+    assert!(json.starts_with(r#"{"request":1,"attempt":1,"outcome":"TimeoutError""#));
+}
+
+#[test]
+fn usage_errors() {
+    assert_eq!(run(&[], "").0, 2);
+    assert_eq!(run(&["simulate", "--bogus"], "").0, 2);
+    assert_eq!(run(&["simulate", "--policy", "/nonexistent.json"], "").0, 2);
+}
+
+#[test]
+fn load_prints_a_simulation_report() {
+    let config = std::env::temp_dir().join("transport-core-cli-load.json");
+    std::fs::write(&config, r#"{"clients": 2, "requests_per_client": 3}"#).unwrap();
+
+    let (code, report) = run(&["load", config.to_str().unwrap()], "");
+    assert_eq!(code, 0);
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["requests"], 6);
+    assert_eq!(report["amplification"], 1.0);
+}
+
+#[test]
+fn replay_exits_with_1_on_differences() {
+    let mut client = Client::new();
+    client.set_recording(true);
+    client.decide(&ctx(), Outcome::TimeoutError, None, None);
+    client.decide(&ctx(), Outcome::Blocked, None, None);
+    let log = client.take_recording();
+
+    let path = std::env::temp_dir().join("transport-core-cli-replay.jsonl");
+    std::fs::write(&path, &log).unwrap();
+    let (code, output) = run(&["replay", path.to_str().unwrap()], "");
+    assert_eq!(code, 0);
+    assert!(output.contains("0 difference(s)"), "{output}");
+
+    std::fs::write(&path, log.replace("HardBlocked", "Unknown")).unwrap();
+    let (code, output) = run(&["replay", path.to_str().unwrap()], "");
+    assert_eq!(code, 1);
+    assert!(
+        output.contains("recorded FAIL (Unknown), replayed FAIL (HardBlocked)"),
+        "{output}"
+    );
+
+    let (code, output) = run(&["replay", "--json", path.to_str().unwrap()], "");
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(report["differences"][0]["entry"], 2);
+
+    assert_eq!(run(&["replay"], "").0, 2);
+}
+
+#[test]
+fn diff_lists_inputs_decided_differently() {
+    let path = std::env::temp_dir().join("transport-core-cli-diff.json");
+    std::fs::write(&path, r#"{"retry": {"hint_mode": "MaxOfHintAndBackoff"}}"#).unwrap();
+    let path = path.to_str().unwrap();
+
+    let (code, output) = run(&["diff", "-", "-"], "");
+    assert_eq!(code, 0);
+    assert_eq!(output, "0 of 3096 input(s) decided differently\n");
+
+    let (code, output) = run(&["diff", "--max-attempts", "2", "-", path], "");
+    assert_eq!(code, 1);
+    assert!(
+        output.contains(
+            "POST attempt 1/2 with key allowing retry 429:500: old RETRY (RateLimited) after 500 ms, \
+             new RETRY (RateLimited) after 1500 ms"
+        ),
+        "{output}"
+    );
+
+    let (code, output) = run(&["diff", "--json", "-", path], "");
+    assert_eq!(code, 1);
+    let diff: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(diff["differences"].as_array().unwrap().len(), 168);
+
+    assert_eq!(run(&["diff", "-"], "").0, 2);
+}
@@ -0,0 +1,119 @@
+use std::ptr;
+
+use transport_core::{
+    auth::AuthDecision,
+    client::Client,
+    concurrency::ConcurrencyPolicy,
+    ffi::{
+        tc_client_free, tc_client_new, tc_client_reset, tc_client_state, tc_client_state_t,
+        tc_last_status, tc_status_t,
+    },
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+};
+
+fn ctx(attempt: u8) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt,
+        max_attempts: 5,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority: Priority::Normal,
+    }
+}
+
+fn unauthorized(client: &mut Client, attempt: u8) -> Decision {
+    client.decide(
+        &ctx(attempt),
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        None,
+    )
+}
+
+#[test]
+fn reset_auth_allows_another_refresh() {
+    let mut client = Client::new();
+
+    assert!(matches!(
+        unauthorized(&mut client, 1),
+        Decision::RefreshAndRetry { .. }
+    ));
+    assert!(client.snapshot().auth.refresh_attempted);
+    assert!(matches!(
+        unauthorized(&mut client, 2),
+        Decision::Fail { .. }
+    ));
+
+    client.reset_auth();
+
+    assert!(!client.snapshot().auth.refresh_attempted);
+    assert!(matches!(
+        unauthorized(&mut client, 3),
+        Decision::RefreshAndRetry { .. }
+    ));
+}
+
+#[test]
+fn reset_keeps_policy_and_outstanding_slots() {
+    let mut client = Client::new();
+    client.set_concurrency_policy(ConcurrencyPolicy {
+        initial_limit: 10,
+        ..ConcurrencyPolicy::default()
+    });
+
+    client.acquire(false, Priority::Normal);
+    client.acquire(false, Priority::Normal);
+    client.release(50, Some(&Outcome::TimeoutError));
+    client.record_attempt(Some("search"), 50, None);
+    client.report_hedge_result(1);
+    unauthorized(&mut client, 1);
+
+    let before = client.snapshot();
+    assert_eq!(before.concurrency_limit, 9);
+    assert_eq!(before.in_flight, 1);
+    assert_eq!(before.partition_count, 1);
+    assert_eq!(before.hedge_stats.hedges_sent, 1);
+
+    client.reset();
+
+    let after = client.snapshot();
+    assert_eq!(after.concurrency_limit, 10);
+    assert_eq!(after.in_flight, 1);
+    assert_eq!(after.partition_count, 0);
+    assert_eq!(after.hedge_stats.requests, 0);
+    assert!(!after.auth.refresh_attempted);
+    assert_eq!(client.policy().concurrency.initial_limit, 10);
+    assert!(client.last_decision().is_none());
+}
+
+#[test]
+fn state_through_the_c_abi() {
+    let client = tc_client_new();
+    let mut state = tc_client_state_t {
+        refresh_in_progress: true,
+        refresh_attempted: true,
+        concurrency_limit: 0,
+        in_flight: 0,
+        queued: 0,
+        partition_count: 0,
+        hedge_requests: 0,
+        hedges_sent: 0,
+        hedges_pending: 0,
+    };
+
+    tc_client_reset(client);
+    tc_client_state(client, &mut state);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+    assert!(!state.refresh_attempted);
+    assert_eq!(
+        state.concurrency_limit,
+        ConcurrencyPolicy::default().initial_limit
+    );
+
+    tc_client_state(client, ptr::null_mut());
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+
+    tc_client_free(client);
+}
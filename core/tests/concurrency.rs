@@ -0,0 +1,208 @@
+use std::os::raw::c_int;
+
+use transport_core::{
+    client::Client,
+    concurrency::{Admission, ConcurrencyPolicy, LimitAlgorithm},
+    ffi::*,
+    model::{Outcome, Priority},
+};
+
+fn client(policy: ConcurrencyPolicy) -> Client {
+    let mut client = Client::new();
+    client.set_concurrency_policy(policy);
+    client
+}
+
+#[test]
+fn acquire_respects_limit_and_queue() {
+    let mut client = client(ConcurrencyPolicy {
+        initial_limit: 2,
+        max_queue: 1,
+        ..ConcurrencyPolicy::default()
+    });
+
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Queued);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Rejected);
+
+    // Still full: the queued request keeps its slot.
+    assert_eq!(client.acquire(true, Priority::Normal), Admission::Queued);
+
+    client.release(10, None);
+    assert_eq!(client.acquire(true, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.concurrency().queued(), 0);
+    assert_eq!(client.concurrency().in_flight(), 2);
+}
+
+#[test]
+fn queued_requests_are_served_before_new_arrivals() {
+    let mut client = client(ConcurrencyPolicy {
+        initial_limit: 1,
+        max_limit: 1,
+        max_queue: 2,
+        ..ConcurrencyPolicy::default()
+    });
+
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Queued);
+
+    // The freed slot is held for the queued request.
+    client.release(10, None);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Queued);
+    assert_eq!(client.acquire(true, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.concurrency().queued(), 1);
+
+    // Once nobody is waiting, new arrivals take free slots directly.
+    client.cancel_queued();
+    client.release(10, None);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+}
+
+#[test]
+fn inverted_bounds_hold_the_limit_at_min_limit() {
+    for algorithm in [LimitAlgorithm::Aimd, LimitAlgorithm::Vegas] {
+        let mut client = client(ConcurrencyPolicy {
+            algorithm,
+            initial_limit: 8,
+            min_limit: 10,
+            max_limit: 5,
+            ..ConcurrencyPolicy::default()
+        });
+        assert_eq!(client.concurrency().limit(), 10);
+
+        for latency_ms in [10, 10, 1_000, 10, 10, 10, 10, 10, 10, 10, 10, 10] {
+            client.acquire(false, Priority::Normal);
+            client.release(latency_ms, None);
+            assert_eq!(client.concurrency().limit(), 10, "{algorithm:?}");
+        }
+        client.acquire(false, Priority::Normal);
+        client.release(10, Some(&Outcome::TimeoutError));
+        assert_eq!(client.concurrency().limit(), 10, "{algorithm:?}");
+    }
+}
+
+#[test]
+fn aimd_backs_off_on_overload_and_recovers_slowly() {
+    let mut client = client(ConcurrencyPolicy {
+        initial_limit: 10,
+        decrease_percent: 50,
+        ..ConcurrencyPolicy::default()
+    });
+
+    client.acquire(false, Priority::Normal);
+    client.release(
+        10,
+        Some(&Outcome::RateLimited {
+            retry_after_ms: None,
+        }),
+    );
+    assert_eq!(client.concurrency().limit(), 5);
+
+    client.acquire(false, Priority::Normal);
+    client.release(10, Some(&Outcome::TimeoutError));
+    assert_eq!(client.concurrency().limit(), 2);
+
+    // Other failures neither grow nor shrink the limit.
+    client.acquire(false, Priority::Normal);
+    client.release(10, Some(&Outcome::HttpStatus(404)));
+    assert_eq!(client.concurrency().limit(), 2);
+
+    for _ in 0..2 {
+        client.acquire(false, Priority::Normal);
+        client.release(10, None);
+    }
+    assert_eq!(client.concurrency().limit(), 3);
+}
+
+#[test]
+fn limit_stays_within_bounds() {
+    let mut client = client(ConcurrencyPolicy {
+        initial_limit: 2,
+        min_limit: 2,
+        max_limit: 3,
+        ..ConcurrencyPolicy::default()
+    });
+
+    for _ in 0..5 {
+        client.acquire(false, Priority::Normal);
+        client.release(10, Some(&Outcome::TimeoutError));
+    }
+    assert_eq!(client.concurrency().limit(), 2);
+
+    for _ in 0..20 {
+        client.acquire(false, Priority::Normal);
+        client.release(10, None);
+    }
+    assert_eq!(client.concurrency().limit(), 3);
+}
+
+#[test]
+fn vegas_shrinks_the_limit_when_latency_rises() {
+    let mut client = client(ConcurrencyPolicy {
+        algorithm: LimitAlgorithm::Vegas,
+        initial_limit: 20,
+        ..ConcurrencyPolicy::default()
+    });
+
+    client.acquire(false, Priority::Normal);
+    client.release(100, None);
+    assert_eq!(client.concurrency().limit(), 21);
+
+    // Latency doubled: about half of the limit is queueing upstream.
+    client.acquire(false, Priority::Normal);
+    client.release(200, None);
+    assert_eq!(client.concurrency().limit(), 20);
+}
+
+#[test]
+fn extreme_limits_do_not_overflow() {
+    let mut aimd = client(ConcurrencyPolicy {
+        initial_limit: u32::MAX,
+        max_limit: u32::MAX,
+        ..ConcurrencyPolicy::default()
+    });
+
+    aimd.acquire(false, Priority::Normal);
+    aimd.release(10, Some(&Outcome::TimeoutError));
+    assert_eq!(aimd.concurrency().limit(), 3_865_470_565);
+
+    let mut vegas = client(ConcurrencyPolicy {
+        algorithm: LimitAlgorithm::Vegas,
+        initial_limit: u32::MAX,
+        max_limit: u32::MAX,
+        ..ConcurrencyPolicy::default()
+    });
+    vegas.acquire(false, Priority::Normal);
+    vegas.release(100, None);
+    assert_eq!(vegas.concurrency().limit(), u32::MAX);
+}
+
+#[test]
+fn extreme_limits_release_through_the_c_abi() {
+    let client = tc_client_new();
+    let policy = tc_concurrency_policy_t {
+        algorithm: tc_limit_algorithm_t::TC_LIMIT_AIMD as c_int,
+        initial_limit: u32::MAX,
+        min_limit: 1,
+        max_limit: u32::MAX,
+        decrease_percent: 10,
+        max_queue: 0,
+        background_share_percent: 80,
+        vegas_alpha: 3,
+        vegas_beta: 6,
+    };
+    tc_client_set_concurrency_policy(client, &policy);
+
+    tc_acquire(client, false, tc_priority_t::TC_PRIORITY_NORMAL as c_int);
+    let timeout = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    };
+    tc_release(client, 10, &timeout);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+    assert_eq!(tc_concurrency_limit(client), 3_865_470_565);
+
+    tc_client_free(client);
+}
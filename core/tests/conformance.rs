@@ -0,0 +1,809 @@
+//! Runs every file in `spec/test-vectors` against the three ways hosts
+//! reach the engine: the Rust API, the C ABI and the JSON entry point.
+//! All three must produce the decision and auth state the vector
+//! expects, and therefore agree with each other.
+//!
+//! Single-step files hold `cases`; `scenarios.json` holds sequences of
+//! steps sharing one client.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::path::Path;
+use std::{fs, ptr};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use transport_core::{
+    auth::{AuthDecision, AuthState},
+    decision::decide_with_policy,
+    error::{classify_http_status, ErrorCategory},
+    ffi::*,
+    json::{decide_json, DecideRequest, DecideResponse},
+    model::{Decision, FailReason, HttpMethod, Outcome, Priority, RequestContext, RetryReason},
+    policy::Policy,
+};
+
+/* ============================
+ * Vector format
+ * ============================ */
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VectorFile {
+    #[allow(dead_code)]
+    description: String,
+    cases: Vec<Case>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Case {
+    name: String,
+    input: Input,
+    expected: Expected,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Input {
+    method: Option<String>,
+    attempt: Option<u8>,
+    max_attempts: Option<u8>,
+    status: Option<u16>,
+    /// A semantic outcome name, or a host error name from
+    /// `spec/error.md`.
+    error: Option<String>,
+    retry_after_ms: Option<u32>,
+    idempotency_key: Option<String>,
+    allow_non_idempotent_retry: Option<bool>,
+    auth_decision: Option<String>,
+    refresh_result: Option<String>,
+    refresh_already_attempted: Option<bool>,
+
+    // Host-side behavior the engine does not model; such cases are
+    // reported as skipped.
+    apply_auth_result: Option<String>,
+    concurrent_requests: Option<u32>,
+    client_instances: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Expected {
+    action: Option<String>,
+    after_ms: Option<u32>,
+    retry_reason: Option<RetryReason>,
+    fail_reason: Option<FailReason>,
+    retryable: Option<bool>,
+    next_attempt: Option<u8>,
+    auth_state: Option<AuthState>,
+    refresh_attempted: Option<bool>,
+    /// Category of an auth failure or of a status code.
+    error_category: Option<ErrorCategory>,
+    /// Category a status maps to (error vectors). For host errors this
+    /// is the host's own mapping, which the engine never sees.
+    category: Option<ErrorCategory>,
+    retry_allowed: Option<RetryAllowed>,
+
+    // Host-side expectations, see `Input`.
+    refresh_count: Option<u32>,
+    waiting_requests: Option<u32>,
+    action_after_refresh: Option<String>,
+    refresh_per_client: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum RetryAllowed {
+    Always(bool),
+    Conditional(Conditional),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Conditional {
+    Conditional,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    #[allow(dead_code)]
+    description: String,
+    scenarios: Vec<Scenario>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    name: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Step {
+    /// Clears the auth state before this step, as a host does after
+    /// obtaining credentials out of band.
+    #[serde(default)]
+    reset_auth: bool,
+    input: Input,
+    expected: Expected,
+}
+
+impl Expected {
+    fn is_host_side(&self) -> bool {
+        self.refresh_count.is_some()
+            || self.waiting_requests.is_some()
+            || self.action_after_refresh.is_some()
+            || self.refresh_per_client.is_some()
+    }
+}
+
+impl Input {
+    fn is_host_side(&self) -> bool {
+        self.apply_auth_result.is_some()
+            || self.concurrent_requests.is_some()
+            || self.client_instances.is_some()
+    }
+
+    fn ctx(&self) -> RequestContext {
+        RequestContext {
+            method: parse_method(self.method.as_deref().unwrap_or("GET")),
+            attempt: self.attempt.unwrap_or(1),
+            max_attempts: self.max_attempts.unwrap_or(3),
+            idempotency_key: self.idempotency_key.clone(),
+            allow_non_idempotent_retry: self.allow_non_idempotent_retry.unwrap_or(false),
+            partition: None,
+            priority: Priority::Normal,
+        }
+    }
+
+    fn auth_decision(&self) -> Option<AuthDecision> {
+        match self.auth_decision.as_deref() {
+            Some("RefreshAndRetry") => Some(AuthDecision::RefreshAndRetry),
+            Some("Fail") => Some(AuthDecision::Fail),
+            None => None,
+            Some(other) => panic!("unknown auth decision: {other}"),
+        }
+    }
+
+    fn refresh_result(&self) -> Option<bool> {
+        match self.refresh_result.as_deref() {
+            Some("SUCCESS") => Some(true),
+            Some("FAILURE") => Some(false),
+            None => None,
+            Some(other) => panic!("unknown refresh result: {other}"),
+        }
+    }
+
+    fn auth_state(&self) -> AuthState {
+        AuthState {
+            refresh_in_progress: false,
+            refresh_attempted: self.refresh_already_attempted.unwrap_or(false),
+        }
+    }
+
+    /// The outcome exactly as described: a semantic outcome or a raw
+    /// status.
+    fn outcome(&self) -> Option<Outcome> {
+        match (&self.error, self.status) {
+            (Some(error), _) => semantic_outcome(error, self.retry_after_ms),
+            (None, Some(status)) => Some(Outcome::HttpStatus(status)),
+            (None, None) => None,
+        }
+    }
+
+    /// Error vectors with a host error the core has no outcome for
+    /// (TLS failures, invalid requests, unknown errors). Nothing about
+    /// them reaches the engine, so they are documentation only.
+    fn is_unmapped_host_error(&self) -> bool {
+        self.error
+            .as_deref()
+            .is_some_and(|error| host_error_outcome(error).is_none())
+    }
+}
+
+fn parse_method(method: &str) -> HttpMethod {
+    match method {
+        "GET" => HttpMethod::GET,
+        "POST" => HttpMethod::POST,
+        "PUT" => HttpMethod::PUT,
+        "DELETE" => HttpMethod::DELETE,
+        "HEAD" => HttpMethod::HEAD,
+        "OPTIONS" => HttpMethod::OPTIONS,
+        other => panic!("unknown method: {other}"),
+    }
+}
+
+fn semantic_outcome(name: &str, retry_after_ms: Option<u32>) -> Option<Outcome> {
+    Some(match name {
+        "NetworkError" => Outcome::NetworkError,
+        "TimeoutError" => Outcome::TimeoutError,
+        "RateLimited" => Outcome::RateLimited { retry_after_ms },
+        "Blocked" => Outcome::Blocked,
+        "Captcha" => Outcome::Captcha,
+        _ => return None,
+    })
+}
+
+/// The outcome `spec/error.md` recommends hosts report for an error.
+fn host_error_outcome(error: &str) -> Option<Outcome> {
+    match error {
+        "connection_reset" | "connection_refused" | "dns_resolution_failure" => {
+            Some(Outcome::NetworkError)
+        }
+        "connect_timeout" | "read_timeout" | "write_timeout" => Some(Outcome::TimeoutError),
+        other => semantic_outcome(other, None),
+    }
+}
+
+/// The outcome a host reports for a status of this category. Fatal and
+/// unknown statuses have none that is retried.
+fn category_outcome(category: &ErrorCategory) -> Option<Outcome> {
+    match category {
+        ErrorCategory::NetworkError => Some(Outcome::NetworkError),
+        ErrorCategory::TimeoutError => Some(Outcome::TimeoutError),
+        ErrorCategory::RateLimitError => Some(Outcome::RateLimited {
+            retry_after_ms: None,
+        }),
+        ErrorCategory::AuthError => Some(Outcome::HttpStatus(401)),
+        ErrorCategory::FatalError | ErrorCategory::UnknownError => None,
+    }
+}
+
+/* ============================
+ * Backends
+ * ============================ */
+
+struct Request {
+    ctx: RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    refresh_result: Option<bool>,
+}
+
+/// One way into the engine, holding the state of one host client.
+trait Backend {
+    fn name(&self) -> &'static str;
+
+    fn set_auth_state(&mut self, state: &AuthState);
+
+    fn reset_auth(&mut self) {
+        self.set_auth_state(&AuthState::new());
+    }
+
+    fn decide(&mut self, request: &Request) -> (Decision, AuthState);
+}
+
+/// `decision::decide_with_policy` with caller-held auth state.
+#[derive(Default)]
+struct RustApi {
+    auth_state: AuthState,
+}
+
+impl Backend for RustApi {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn set_auth_state(&mut self, state: &AuthState) {
+        self.auth_state = state.clone();
+    }
+
+    fn decide(&mut self, request: &Request) -> (Decision, AuthState) {
+        let decision = decide_with_policy(
+            &request.ctx,
+            request.outcome.clone(),
+            request.auth_decision,
+            &mut self.auth_state,
+            request.refresh_result,
+            &Policy::default(),
+        );
+        (decision, self.auth_state.clone())
+    }
+}
+
+/// `tc_decide` and the getters, on a `tc_client_new` client.
+struct CAbi {
+    client: *mut transport_core_client,
+}
+
+impl Default for CAbi {
+    fn default() -> Self {
+        Self {
+            client: tc_client_new(),
+        }
+    }
+}
+
+impl Drop for CAbi {
+    fn drop(&mut self) {
+        tc_client_free(self.client);
+    }
+}
+
+impl Backend for CAbi {
+    fn name(&self) -> &'static str {
+        "c"
+    }
+
+    fn set_auth_state(&mut self, state: &AuthState) {
+        let exported = tc_client_export_state(self.client, 0);
+        let mut document: Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(exported) }.to_str().unwrap()).unwrap();
+        tc_string_free(exported);
+
+        document["auth"] = serde_json::to_value(state).unwrap();
+        let document = CString::new(document.to_string()).unwrap();
+        assert!(tc_client_import_state(self.client, document.as_ptr()));
+    }
+
+    fn reset_auth(&mut self) {
+        tc_client_reset_auth(self.client);
+    }
+
+    fn decide(&mut self, request: &Request) -> (Decision, AuthState) {
+        let ctx = &request.ctx;
+        let key = ctx
+            .idempotency_key
+            .as_deref()
+            .map(|key| CString::new(key).unwrap());
+        let c_ctx = tc_request_context_t {
+            method: method_to_c(&ctx.method) as c_int,
+            attempt: ctx.attempt,
+            max_attempts: ctx.max_attempts,
+            allow_non_idempotent_retry: ctx.allow_non_idempotent_retry,
+            idempotency_key: key.as_ref().map_or(ptr::null(), |key| key.as_ptr()),
+            partition: ptr::null(),
+            priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+        };
+        let auth_decision = match request.auth_decision {
+            Some(AuthDecision::RefreshAndRetry) => tc_auth_decision_t::TC_AUTH_REFRESH_AND_RETRY,
+            Some(AuthDecision::Fail) | None => tc_auth_decision_t::TC_AUTH_FAIL,
+        };
+        let refresh_result = match request.refresh_result {
+            Some(true) => 1,
+            Some(false) => 0,
+            None => -1,
+        };
+
+        let coarse = tc_decide(
+            self.client,
+            &c_ctx,
+            &outcome_to_c(&request.outcome),
+            auth_decision as c_int,
+            refresh_result,
+        );
+        assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+
+        let decision = match coarse {
+            tc_decision_t::TC_DECISION_PROCEED => Decision::Proceed,
+            tc_decision_t::TC_DECISION_RETRY => Decision::Retry {
+                after_ms: tc_last_retry_after_ms(self.client),
+                reason: retry_reason(tc_last_retry_reason(self.client)),
+            },
+            tc_decision_t::TC_DECISION_REFRESH_AND_RETRY => Decision::RefreshAndRetry {
+                after_ms: tc_last_retry_after_ms(self.client),
+            },
+            tc_decision_t::TC_DECISION_FAIL => Decision::Fail {
+                reason: fail_reason(tc_last_fail_reason(self.client)),
+                retryable: tc_last_fail_retryable(self.client),
+            },
+        };
+
+        let mut state = std::mem::MaybeUninit::<tc_client_state_t>::uninit();
+        tc_client_state(self.client, state.as_mut_ptr());
+        let state = unsafe { state.assume_init() };
+
+        (
+            decision,
+            AuthState {
+                refresh_in_progress: state.refresh_in_progress,
+                refresh_attempted: state.refresh_attempted,
+            },
+        )
+    }
+}
+
+fn method_to_c(method: &HttpMethod) -> tc_http_method_t {
+    match method {
+        HttpMethod::GET => tc_http_method_t::TC_HTTP_GET,
+        HttpMethod::POST => tc_http_method_t::TC_HTTP_POST,
+        HttpMethod::PUT => tc_http_method_t::TC_HTTP_PUT,
+        HttpMethod::DELETE => tc_http_method_t::TC_HTTP_DELETE,
+        HttpMethod::HEAD => tc_http_method_t::TC_HTTP_HEAD,
+        HttpMethod::OPTIONS => tc_http_method_t::TC_HTTP_OPTIONS,
+    }
+}
+
+fn outcome_to_c(outcome: &Outcome) -> tc_outcome_t {
+    let (kind, http_status, retry_after_ms) = match outcome {
+        Outcome::NetworkError => (tc_outcome_kind_t::TC_OUTCOME_NETWORK_ERROR, 0, 0),
+        Outcome::TimeoutError => (tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR, 0, 0),
+        Outcome::RateLimited { retry_after_ms } => (
+            tc_outcome_kind_t::TC_OUTCOME_RATE_LIMITED,
+            0,
+            retry_after_ms.unwrap_or(0),
+        ),
+        Outcome::Blocked => (tc_outcome_kind_t::TC_OUTCOME_BLOCKED, 0, 0),
+        Outcome::Captcha => (tc_outcome_kind_t::TC_OUTCOME_CAPTCHA, 0, 0),
+        Outcome::HttpStatus(status) => (tc_outcome_kind_t::TC_OUTCOME_HTTP_STATUS, *status, 0),
+    };
+
+    tc_outcome_t {
+        kind: kind as c_int,
+        http_status,
+        retry_after_ms,
+    }
+}
+
+fn retry_reason(value: u8) -> RetryReason {
+    [
+        RetryReason::NetworkError,
+        RetryReason::Timeout,
+        RetryReason::RateLimited,
+        RetryReason::AuthExpired,
+    ]
+    .into_iter()
+    .find(|reason| *reason as u8 == value)
+    .unwrap_or_else(|| panic!("unknown tc_retry_reason_t {value}"))
+}
+
+fn fail_reason(value: u8) -> FailReason {
+    [
+        FailReason::MaxAttemptsExceeded,
+        FailReason::AuthFailed,
+        FailReason::HardBlocked,
+        FailReason::Shed,
+        FailReason::Unknown,
+    ]
+    .into_iter()
+    .find(|reason| *reason as u8 == value)
+    .unwrap_or_else(|| panic!("unknown tc_fail_reason_t {value}"))
+}
+
+/// The stateless `json::decide_json`, passing the returned auth state
+/// back in like a scripting host would.
+#[derive(Default)]
+struct JsonEntry {
+    auth_state: AuthState,
+}
+
+impl Backend for JsonEntry {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn set_auth_state(&mut self, state: &AuthState) {
+        self.auth_state = state.clone();
+    }
+
+    fn decide(&mut self, request: &Request) -> (Decision, AuthState) {
+        let input = serde_json::to_string(&DecideRequest {
+            ctx: request.ctx.clone(),
+            outcome: request.outcome.clone(),
+            auth_decision: request.auth_decision,
+            refresh_result: request.refresh_result,
+            auth_state: Some(self.auth_state.clone()),
+            policy: None,
+        })
+        .unwrap();
+
+        match serde_json::from_str(&decide_json(&input)).unwrap() {
+            DecideResponse::Ok {
+                decision,
+                auth_state,
+            } => {
+                self.auth_state = auth_state.clone();
+                (decision, auth_state)
+            }
+            DecideResponse::Error { error } => panic!("decide_json rejected {input}: {error}"),
+        }
+    }
+}
+
+fn backends() -> Vec<Box<dyn Backend>> {
+    vec![
+        Box::<RustApi>::default(),
+        Box::<CAbi>::default(),
+        Box::<JsonEntry>::default(),
+    ]
+}
+
+/* ============================
+ * Checks
+ * ============================ */
+
+fn action(decision: &Decision) -> &'static str {
+    match decision {
+        Decision::Proceed => "PROCEED",
+        Decision::Retry { .. } => "RETRY",
+        Decision::RefreshAndRetry { .. } => "REFRESH_AND_RETRY",
+        Decision::Fail { .. } => "FAIL",
+    }
+}
+
+/// Checks one decision against `expected`, returning a description of
+/// every mismatch.
+fn check_decision(
+    input: &Input,
+    expected: &Expected,
+    decision: &Decision,
+    auth_state: &AuthState,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut check = |what: &str, ok: bool, want: &dyn std::fmt::Debug| {
+        if !ok {
+            errors.push(format!("{what}: expected {want:?}, got {decision:?}"));
+        }
+    };
+
+    if let Some(want) = &expected.action {
+        check("action", action(decision) == want, want);
+    }
+    if let Some(want) = expected.after_ms {
+        let after_ms = match decision {
+            Decision::Retry { after_ms, .. } | Decision::RefreshAndRetry { after_ms } => {
+                Some(*after_ms)
+            }
+            _ => None,
+        };
+        check("after_ms", after_ms == Some(want), &want);
+    }
+    if let Some(want) = expected.retry_reason {
+        let ok = matches!(decision, Decision::Retry { reason, .. } if *reason == want);
+        check("retry_reason", ok, &want);
+    }
+    if let Some(want) = expected.fail_reason {
+        let ok = matches!(decision, Decision::Fail { reason, .. } if *reason == want);
+        check("fail_reason", ok, &want);
+    }
+    if let Some(want) = expected.retryable {
+        let retryable = match decision {
+            Decision::Fail { retryable, .. } => *retryable,
+            _ => true,
+        };
+        check("retryable", retryable == want, &want);
+    }
+    if let Some(want) = expected.next_attempt {
+        // The host increments the attempt; a retry is sent as the next one.
+        let retries = matches!(
+            decision,
+            Decision::Retry { .. } | Decision::RefreshAndRetry { .. }
+        );
+        let ok = retries && Some(want) == input.ctx().attempt.checked_add(1);
+        check("next_attempt", ok, &want);
+    }
+    if let Some(category) = &expected.error_category {
+        match (input.status, decision) {
+            (Some(status), _) => {
+                let actual = classify_http_status(status);
+                check("error_category", actual == *category, category);
+            }
+            (None, Decision::Fail { .. }) => {}
+            (None, _) => check("error_category", false, category),
+        }
+    }
+
+    if let Some(want) = &expected.auth_state {
+        if auth_state != want {
+            errors.push(format!("auth_state: expected {want:?}, got {auth_state:?}"));
+        }
+    }
+    if let Some(want) = expected.refresh_attempted {
+        if auth_state.refresh_attempted != want {
+            errors.push(format!(
+                "refresh_attempted: expected {want}, got {auth_state:?}"
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Error vectors: the category a status maps to, and whether the
+/// outcome a host reports for the status or host error is retried.
+fn check_category(input: &Input, expected: &Expected, backend: &mut dyn Backend) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let outcome = match (&input.error, input.status) {
+        (Some(error), _) => host_error_outcome(error),
+        (None, Some(status)) => {
+            let actual = classify_http_status(status);
+            if let Some(want) = &expected.category {
+                if actual != *want {
+                    errors.push(format!("category: expected {want:?}, got {actual:?}"));
+                }
+            }
+            category_outcome(&actual)
+        }
+        (None, None) => {
+            return vec!["category expectations need a status or an error".to_string()];
+        }
+    };
+
+    let Some(retry_allowed) = expected.retry_allowed else {
+        return errors;
+    };
+    let Some(outcome) = outcome else {
+        if retry_allowed != RetryAllowed::Always(false) {
+            errors.push(format!("{input:?} has no outcome, so it is never retried"));
+        }
+        return errors;
+    };
+
+    let attempts: &[(Option<AuthDecision>, &str)] = match retry_allowed {
+        RetryAllowed::Always(true) => &[(None, "RETRY")],
+        RetryAllowed::Always(false) => &[(None, "FAIL")],
+        RetryAllowed::Conditional(Conditional::Conditional) => &[
+            (Some(AuthDecision::RefreshAndRetry), "REFRESH_AND_RETRY"),
+            (None, "FAIL"),
+        ],
+    };
+    for (auth_decision, want) in attempts {
+        backend.set_auth_state(&AuthState::new());
+        let (decision, _) = backend.decide(&Request {
+            ctx: input.ctx(),
+            outcome: outcome.clone(),
+            auth_decision: *auth_decision,
+            refresh_result: None,
+        });
+        if action(&decision) != *want {
+            errors.push(format!(
+                "retry_allowed ({auth_decision:?}): expected {want}, got {decision:?}"
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Decides `input` with the backend's current state.
+fn decide_input(backend: &mut dyn Backend, input: &Input) -> (Decision, AuthState) {
+    let Some(outcome) = input.outcome() else {
+        panic!("no outcome in {input:?}");
+    };
+    backend.decide(&Request {
+        ctx: input.ctx(),
+        outcome,
+        auth_decision: input.auth_decision(),
+        refresh_result: input.refresh_result(),
+    })
+}
+
+fn load<T: serde::de::DeserializeOwned>(file: &str) -> T {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../spec/test-vectors")
+        .join(file);
+    let raw = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    serde_json::from_str(&raw).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// Runs one vector file, returning the names of skipped cases.
+fn run_file(file: &str) -> Vec<String> {
+    let vectors: VectorFile = load(file);
+
+    let mut failures = Vec::new();
+    let mut skipped = Vec::new();
+
+    for case in &vectors.cases {
+        if case.input.is_host_side()
+            || case.expected.is_host_side()
+            || case.input.is_unmapped_host_error()
+        {
+            skipped.push(case.name.clone());
+            continue;
+        }
+
+        for mut backend in backends() {
+            let errors =
+                if case.expected.category.is_some() || case.expected.retry_allowed.is_some() {
+                    check_category(&case.input, &case.expected, backend.as_mut())
+                } else {
+                    backend.set_auth_state(&case.input.auth_state());
+                    let (decision, auth_state) = decide_input(backend.as_mut(), &case.input);
+                    check_decision(&case.input, &case.expected, &decision, &auth_state)
+                };
+
+            for error in errors {
+                failures.push(format!(
+                    "{file}/{} [{}]: {error}",
+                    case.name,
+                    backend.name()
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    skipped
+}
+
+/// Runs every scenario in `file`, each on one fresh client per backend.
+fn run_scenarios(file: &str) {
+    let vectors: ScenarioFile = load(file);
+    let mut failures = Vec::new();
+
+    for scenario in &vectors.scenarios {
+        for mut backend in backends() {
+            for (i, step) in scenario.steps.iter().enumerate() {
+                assert!(
+                    step.input.refresh_already_attempted.is_none(),
+                    "{}: scenario state comes from earlier steps",
+                    scenario.name
+                );
+                if step.reset_auth {
+                    backend.reset_auth();
+                }
+
+                let (decision, auth_state) = decide_input(backend.as_mut(), &step.input);
+                for error in check_decision(&step.input, &step.expected, &decision, &auth_state) {
+                    failures.push(format!(
+                        "{file}/{} step {} [{}]: {error}",
+                        scenario.name,
+                        i + 1,
+                        backend.name()
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn every_vector_file_is_covered() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../spec/test-vectors");
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".json"))
+        .collect();
+    files.sort();
+
+    assert_eq!(
+        files,
+        ["auth.json", "error.json", "retry.json", "scenarios.json"]
+    );
+}
+
+#[test]
+fn retry_vectors() {
+    assert!(run_file("retry.json").is_empty());
+}
+
+#[test]
+fn auth_vectors() {
+    // Auth application, single-flight refresh across requests and
+    // per-client isolation happen in the host.
+    assert_eq!(
+        run_file("auth.json"),
+        [
+            "apply_auth_before_request",
+            "fail_when_auth_application_fails",
+            "single_flight_refresh_blocks_concurrent_requests",
+            "auth_state_shared_within_client_only",
+        ]
+    );
+}
+
+#[test]
+fn error_vectors() {
+    // Hosts fail these without consulting the engine.
+    assert_eq!(
+        run_file("error.json"),
+        [
+            "map_tls_validation_failure_to_fatal_error",
+            "map_invalid_request_to_fatal_error",
+            "map_unknown_error_to_unknown_error",
+        ]
+    );
+}
+
+#[test]
+fn scenario_vectors() {
+    run_scenarios("scenarios.json");
+}
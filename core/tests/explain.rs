@@ -0,0 +1,205 @@
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::ptr;
+
+use serde_json::{json, Value};
+
+use transport_core::{
+    auth::{AuthDecision, AuthState},
+    client::Client,
+    concurrency::ConcurrencyPolicy,
+    decision::{decide_explained, decide_with_policy},
+    ffi::*,
+    model::{HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+};
+
+fn ctx(method: HttpMethod, attempt: u8) -> RequestContext {
+    RequestContext {
+        method,
+        attempt,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority: Priority::Normal,
+    }
+}
+
+fn rules(explanation: &Value) -> Vec<&str> {
+    explanation["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|step| step["rule"].as_str().unwrap())
+        .collect()
+}
+
+fn explain(ctx: &RequestContext, outcome: Outcome) -> Value {
+    let (_, explanation) = decide_explained(
+        ctx,
+        outcome,
+        None,
+        &mut AuthState::new(),
+        None,
+        &Policy::default(),
+    );
+    serde_json::from_str(&explanation.to_json()).unwrap()
+}
+
+#[test]
+fn explained_decision_matches_plain_decision() {
+    let ctx = ctx(HttpMethod::GET, 1);
+    let outcome = Outcome::RateLimited {
+        retry_after_ms: Some(3_000),
+    };
+
+    let plain = decide_with_policy(
+        &ctx,
+        outcome.clone(),
+        None,
+        &mut AuthState::new(),
+        None,
+        &Policy::default(),
+    );
+    let (explained, explanation) = decide_explained(
+        &ctx,
+        outcome,
+        None,
+        &mut AuthState::new(),
+        None,
+        &Policy::default(),
+    );
+
+    assert_eq!(plain, explained);
+    assert_eq!(explanation.decision, plain);
+}
+
+#[test]
+fn non_idempotent_failure_names_the_idempotency_rule() {
+    let explanation = explain(&ctx(HttpMethod::POST, 1), Outcome::NetworkError);
+
+    assert_eq!(
+        rules(&explanation),
+        ["classification", "attempt_limit", "idempotency"]
+    );
+    assert_eq!(explanation["steps"][2]["retryable"], false);
+    assert_eq!(
+        explanation["decision"],
+        json!({"Fail": {"reason": "MaxAttemptsExceeded", "retryable": false}})
+    );
+}
+
+#[test]
+fn exhausted_attempts_stop_at_the_limit() {
+    let explanation = explain(&ctx(HttpMethod::GET, 3), Outcome::TimeoutError);
+
+    assert_eq!(rules(&explanation), ["classification", "attempt_limit"]);
+    assert_eq!(explanation["steps"][1]["exhausted"], true);
+}
+
+#[test]
+fn http_status_classification_and_hint_clamping() {
+    let explanation = explain(&ctx(HttpMethod::GET, 1), Outcome::HttpStatus(404));
+    assert_eq!(explanation["steps"][0]["category"], "FatalError");
+
+    let explanation = explain(
+        &ctx(HttpMethod::GET, 1),
+        Outcome::RateLimited {
+            retry_after_ms: Some(600_000),
+        },
+    );
+    let hint = &explanation["steps"][3];
+    assert_eq!(hint["rule"], "retry_after_hint");
+    assert_eq!(hint["clamped"], true);
+    assert_eq!(hint["after_ms"], 120_000);
+}
+
+#[test]
+fn auth_step_records_state_before_the_decision() {
+    let mut auth_state = AuthState::new();
+    auth_state.refresh_attempted = true;
+
+    let (_, explanation) = decide_explained(
+        &ctx(HttpMethod::GET, 1),
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        &mut auth_state,
+        None,
+        &Policy::default(),
+    );
+    let explanation: Value = serde_json::from_str(&explanation.to_json()).unwrap();
+
+    assert_eq!(explanation["steps"][1]["rule"], "auth");
+    assert_eq!(explanation["steps"][1]["refresh_attempted"], true);
+}
+
+#[test]
+fn client_records_shedding_only_when_enabled() {
+    let mut client = Client::new();
+    client.set_concurrency_policy(ConcurrencyPolicy {
+        initial_limit: 1,
+        ..ConcurrencyPolicy::default()
+    });
+    client.acquire(false, Priority::Normal);
+
+    let mut background = ctx(HttpMethod::GET, 1);
+    background.priority = Priority::Background;
+
+    client.decide(&background, Outcome::NetworkError, None, None);
+    assert!(client.last_explanation().is_none());
+
+    client.set_explain(true);
+    client.decide(&background, Outcome::NetworkError, None, None);
+
+    let explanation: Value =
+        serde_json::from_str(&client.last_explanation().unwrap().to_json()).unwrap();
+    let shedding = explanation["steps"].as_array().unwrap().last().unwrap();
+    assert_eq!(shedding["rule"], "load_shedding");
+    assert_eq!(shedding["shed"], true);
+}
+
+#[test]
+fn explanation_through_the_c_abi() {
+    let client = tc_client_new();
+    let ctx = tc_request_context_t {
+        method: tc_http_method_t::TC_HTTP_GET as c_int,
+        attempt: 1,
+        max_attempts: 3,
+        allow_non_idempotent_retry: false,
+        idempotency_key: ptr::null(),
+        partition: ptr::null(),
+        priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+    };
+    let outcome = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_BLOCKED as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    };
+    let decide = || {
+        tc_decide(
+            client,
+            &ctx,
+            &outcome,
+            tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+            -1,
+        )
+    };
+
+    decide();
+    assert!(tc_last_explanation_json(client).is_null());
+
+    tc_client_set_explain(client, true);
+    decide();
+
+    let output = tc_last_explanation_json(client);
+    assert!(!output.is_null());
+    let explanation: Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(output) }.to_str().unwrap()).unwrap();
+    tc_string_free(output);
+
+    assert_eq!(rules(&explanation), ["classification"]);
+    assert_eq!(explanation["steps"][0]["outcome"], "Blocked");
+
+    tc_client_free(client);
+}
@@ -0,0 +1,156 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::ptr;
+
+use transport_core::ffi::*;
+
+fn ctx() -> tc_request_context_t {
+    tc_request_context_t {
+        method: tc_http_method_t::TC_HTTP_GET as c_int,
+        attempt: 1,
+        max_attempts: 3,
+        allow_non_idempotent_retry: false,
+        idempotency_key: ptr::null(),
+        partition: ptr::null(),
+        priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+    }
+}
+
+fn network_error() -> tc_outcome_t {
+    tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_NETWORK_ERROR as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    }
+}
+
+fn last_error() -> String {
+    let message = tc_last_error_message();
+    assert!(!message.is_null());
+    unsafe { CStr::from_ptr(message) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn decide(client: *mut transport_core_client, ctx: &tc_request_context_t) -> tc_decision_t {
+    tc_decide(
+        client,
+        ctx,
+        &network_error(),
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    )
+}
+
+#[test]
+fn success_clears_the_status() {
+    let client = tc_client_new();
+
+    assert_eq!(decide(client, &ctx()), tc_decision_t::TC_DECISION_RETRY);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+    assert!(tc_last_error_message().is_null());
+
+    tc_client_free(client);
+}
+
+#[test]
+fn null_pointers_are_invalid_arguments() {
+    let client = tc_client_new();
+
+    assert_eq!(
+        decide(ptr::null_mut(), &ctx()),
+        tc_decision_t::TC_DECISION_FAIL
+    );
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+    assert_eq!(last_error(), "client is NULL");
+
+    tc_decide(
+        client,
+        &ctx(),
+        ptr::null(),
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+    assert_eq!(last_error(), "outcome is NULL");
+
+    // The client was not touched by the rejected calls.
+    assert_eq!(tc_last_retry_reason(client), 0);
+
+    tc_client_free(client);
+}
+
+#[test]
+fn out_of_range_enums_are_rejected() {
+    let client = tc_client_new();
+
+    let mut bad_method = ctx();
+    bad_method.method = 42;
+    decide(client, &bad_method);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ENUM);
+    assert_eq!(last_error(), "42 is not a valid tc_http_method_t");
+
+    let mut bad_outcome = network_error();
+    bad_outcome.kind = -1;
+    tc_decide(
+        client,
+        &ctx(),
+        &bad_outcome,
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ENUM);
+
+    assert_eq!(
+        tc_acquire(client, false, 7),
+        tc_admission_t::TC_ADMISSION_REJECTED
+    );
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ENUM);
+
+    tc_client_free(client);
+}
+
+#[test]
+fn refresh_result_must_be_tri_state() {
+    let client = tc_client_new();
+
+    tc_decide(
+        client,
+        &ctx(),
+        &network_error(),
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        2,
+    );
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+
+    tc_client_free(client);
+}
+
+#[test]
+fn idempotency_key_is_passed_through() {
+    let client = tc_client_new();
+    let key = CString::new("order-42").unwrap();
+
+    let mut post = ctx();
+    post.method = tc_http_method_t::TC_HTTP_POST as c_int;
+    post.allow_non_idempotent_retry = true;
+    assert_eq!(decide(client, &post), tc_decision_t::TC_DECISION_FAIL);
+
+    post.idempotency_key = key.as_ptr();
+    assert_eq!(decide(client, &post), tc_decision_t::TC_DECISION_RETRY);
+
+    tc_client_free(client);
+}
+
+#[test]
+fn invalid_utf8_strings_are_rejected() {
+    let client = tc_client_new();
+    let partition = CString::new(vec![0xff, 0xfe]).unwrap();
+
+    assert_eq!(tc_recommended_timeout_ms(client, partition.as_ptr()), 0);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_UTF8);
+    assert_eq!(last_error(), "partition is not valid UTF-8");
+
+    tc_client_free(client);
+}
@@ -0,0 +1,75 @@
+//! Inputs the fuzz targets under `fuzz/` once crashed on, replayed
+//! through the same calls the targets make so they keep passing without
+//! a nightly toolchain.
+
+use std::ffi::CStr;
+use std::os::raw::c_int;
+
+use transport_core::{ffi::*, replay};
+
+#[path = "../fuzz/src/lib.rs"]
+mod fuzz_checks;
+
+/// A limit of `u32::MAX` overflowed both the background share and the
+/// multiplicative decrease.
+const EXTREME_LIMITS: &str =
+    r#"{"concurrency":{"initial_limit":4294967295,"max_limit":4294967295}}"#;
+
+fn run_policy_json(input: &str) {
+    fuzz_checks::check_policy(serde_json::from_str(input).unwrap());
+}
+
+#[test]
+fn policy_json_extreme_concurrency_limits() {
+    run_policy_json(EXTREME_LIMITS);
+    for algorithm in ["Aimd", "Vegas"] {
+        run_policy_json(&format!(
+            r#"{{"concurrency":{{"algorithm":"{algorithm}","initial_limit":4294967295,"max_limit":4294967295,"background_share_percent":100,"decrease_percent":0}}}}"#
+        ));
+    }
+}
+
+#[test]
+fn c_abi_release_with_timeout_under_extreme_limits() {
+    let client = tc_client_new();
+    tc_client_set_explain(client, true);
+    tc_client_set_recording(client, true);
+
+    let policy = tc_concurrency_policy_t {
+        algorithm: tc_limit_algorithm_t::TC_LIMIT_AIMD as c_int,
+        initial_limit: u32::MAX,
+        min_limit: 0,
+        max_limit: u32::MAX,
+        decrease_percent: 0,
+        max_queue: u32::MAX,
+        background_share_percent: u8::MAX,
+        vegas_alpha: u32::MAX,
+        vegas_beta: u32::MAX,
+    };
+    tc_client_set_concurrency_policy(client, &policy);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+
+    let timeout = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    };
+    for priority in [
+        tc_priority_t::TC_PRIORITY_BACKGROUND,
+        tc_priority_t::TC_PRIORITY_NORMAL,
+        tc_priority_t::TC_PRIORITY_CRITICAL,
+    ] {
+        tc_acquire(client, false, priority as c_int);
+        assert_ne!(tc_last_status(), tc_status_t::TC_STATUS_PANIC);
+        tc_release(client, u32::MAX, &timeout);
+        assert_ne!(tc_last_status(), tc_status_t::TC_STATUS_PANIC);
+    }
+
+    let log = tc_client_take_recording(client);
+    let text = unsafe { CStr::from_ptr(log) }.to_str().unwrap().to_string();
+    tc_string_free(log);
+    let report = replay::replay(&replay::parse(&text).unwrap(), None);
+    assert!(report.is_identical(), "{:?}", report.differences);
+
+    tc_client_free(client);
+}
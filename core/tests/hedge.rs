@@ -0,0 +1,210 @@
+use std::ptr;
+
+use transport_core::{
+    client::Client,
+    ffi::*,
+    hedge::{plan_hedge, plan_hedge_with_stats, HedgePolicy, HedgeStats},
+    model::{HttpMethod, Priority, RequestContext},
+    policy::Policy,
+    stats::{LatencyStats, TimeoutPolicy},
+};
+
+fn ctx(method: HttpMethod) -> RequestContext {
+    RequestContext {
+        method,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority: Priority::Normal,
+    }
+}
+
+const POLICY: HedgePolicy = HedgePolicy {
+    max_hedges: 2,
+    delay_ms: 50,
+    delay_percentile: 0,
+    min_samples: 0,
+    budget_percent: 0,
+};
+
+#[test]
+fn hedging_is_disabled_by_default() {
+    assert_eq!(
+        plan_hedge(&ctx(HttpMethod::GET), 0, &HedgePolicy::default()),
+        None
+    );
+}
+
+#[test]
+fn idempotent_requests_hedge_up_to_max_hedges() {
+    let get = ctx(HttpMethod::GET);
+
+    assert_eq!(plan_hedge(&get, 0, &POLICY), Some(50));
+    assert_eq!(plan_hedge(&get, 1, &POLICY), Some(50));
+    assert_eq!(plan_hedge(&get, 2, &POLICY), None);
+}
+
+#[test]
+fn non_idempotent_requests_are_never_hedged() {
+    let mut post = ctx(HttpMethod::POST);
+    post.idempotency_key = Some("abc123".to_string());
+    post.allow_non_idempotent_retry = true;
+
+    assert_eq!(plan_hedge(&post, 0, &POLICY), None);
+}
+
+#[test]
+fn client_accounts_for_reported_hedges() {
+    let mut client = Client::with_policy(Policy {
+        hedge: POLICY,
+        ..Policy::default()
+    });
+
+    assert_eq!(client.plan_hedge(&ctx(HttpMethod::HEAD), 0), Some(50));
+    assert_eq!(client.plan_hedge(&ctx(HttpMethod::HEAD), 1), Some(50));
+    assert_eq!(client.hedge_stats().hedges_pending, 2);
+
+    client.report_hedge_result(1);
+    client.report_hedge_result(2);
+    client.report_hedge_result(0);
+
+    assert_eq!(
+        client.hedge_stats(),
+        &HedgeStats {
+            requests: 3,
+            hedges_sent: 3,
+            hedges_pending: 0,
+        }
+    );
+}
+
+#[test]
+fn percentile_delay_follows_partition_latencies() {
+    let policy = HedgePolicy {
+        delay_percentile: 90,
+        min_samples: 10,
+        ..POLICY
+    };
+    let mut latency = LatencyStats::new();
+    let mut get = ctx(HttpMethod::GET);
+    get.partition = Some("search".to_string());
+
+    for ms in 1..=9 {
+        latency.record(Some("search"), ms * 10, None, &TimeoutPolicy::default());
+    }
+    // Too few samples yet: the fixed delay applies.
+    let stats = HedgeStats::default();
+    assert_eq!(
+        plan_hedge_with_stats(&get, 0, &policy, &latency, &stats),
+        Some(50)
+    );
+
+    latency.record(Some("search"), 100, None, &TimeoutPolicy::default());
+    assert_eq!(
+        plan_hedge_with_stats(&get, 0, &policy, &latency, &stats),
+        Some(90)
+    );
+    // Other partitions keep the fixed delay.
+    assert_eq!(
+        plan_hedge_with_stats(&ctx(HttpMethod::GET), 0, &policy, &latency, &stats),
+        Some(50)
+    );
+    // The stateless planner knows no latencies.
+    assert_eq!(plan_hedge(&get, 0, &policy), Some(50));
+}
+
+#[test]
+fn budget_caps_hedges_by_reported_requests() {
+    let mut client = Client::with_policy(Policy {
+        hedge: HedgePolicy {
+            budget_percent: 10,
+            ..POLICY
+        },
+        ..Policy::default()
+    });
+    let get = ctx(HttpMethod::GET);
+
+    // One hedge fits into 10% once nine requests plus the planned one
+    // were seen.
+    assert_eq!(client.plan_hedge(&get, 0), None);
+    for _ in 0..9 {
+        client.report_hedge_result(0);
+    }
+    assert_eq!(client.plan_hedge(&get, 0), Some(50));
+
+    client.report_hedge_result(1);
+    assert_eq!(client.plan_hedge(&get, 0), None);
+    for _ in 0..9 {
+        client.report_hedge_result(0);
+    }
+    assert_eq!(client.plan_hedge(&get, 0), Some(50));
+}
+
+#[test]
+fn planned_hedges_count_against_the_budget_before_results() {
+    let mut client = Client::with_policy(Policy {
+        hedge: HedgePolicy {
+            budget_percent: 10,
+            ..POLICY
+        },
+        ..Policy::default()
+    });
+    let get = ctx(HttpMethod::GET);
+    for _ in 0..99 {
+        client.report_hedge_result(0);
+    }
+
+    // A burst planned before any of its results comes back.
+    let planned = (0..50).filter(|_| client.plan_hedge(&get, 0).is_some());
+    assert_eq!(planned.count(), 10);
+    assert_eq!(client.hedge_stats().hedges_pending, 10);
+
+    // Results release what was planned but not sent.
+    for _ in 0..10 {
+        client.report_hedge_result(0);
+    }
+    assert_eq!(client.hedge_stats().hedges_pending, 0);
+    assert_eq!(client.plan_hedge(&get, 0), Some(50));
+}
+
+#[test]
+fn extreme_stats_do_not_overflow_the_budget() {
+    let policy = HedgePolicy {
+        budget_percent: u32::MAX,
+        ..POLICY
+    };
+    let stats = HedgeStats {
+        requests: u64::MAX,
+        hedges_sent: u64::MAX,
+        hedges_pending: u64::MAX,
+    };
+
+    assert_eq!(
+        plan_hedge_with_stats(
+            &ctx(HttpMethod::GET),
+            0,
+            &policy,
+            &LatencyStats::new(),
+            &stats
+        ),
+        Some(50)
+    );
+}
+
+#[test]
+fn hedge_adaptation_rejects_percentiles_over_100() {
+    let client = tc_client_new();
+
+    tc_client_set_hedge_adaptation(client, 101, 10, 5);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+    tc_client_set_hedge_adaptation(client, 0, 10, 5);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+    tc_client_set_hedge_adaptation(client, 100, 10, 5);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_OK);
+
+    tc_client_set_hedge_adaptation(ptr::null_mut(), 50, 10, 5);
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+    tc_client_free(client);
+}
@@ -0,0 +1,139 @@
+use transport_core::{
+    auth::AuthState,
+    client::Client,
+    decision::decide_with_policy,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext, RetryReason},
+    policy::Policy,
+    replay,
+    retry::{HintMode, RetryPolicy},
+};
+
+fn ctx(attempt: u8) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority: Priority::Normal,
+    }
+}
+
+fn rate_limited_delay(hint: u32, retry: RetryPolicy) -> u32 {
+    let policy = Policy {
+        retry,
+        ..Policy::default()
+    };
+    let decision = decide_with_policy(
+        &ctx(1),
+        Outcome::RateLimited {
+            retry_after_ms: Some(hint),
+        },
+        None,
+        &mut AuthState::new(),
+        None,
+        &policy,
+    );
+
+    match decision {
+        Decision::Retry {
+            after_ms,
+            reason: RetryReason::RateLimited,
+        } => after_ms,
+        other => panic!("expected rate-limited retry, got {:?}", other),
+    }
+}
+
+#[test]
+fn hint_only_is_the_default() {
+    assert_eq!(rate_limited_delay(300, RetryPolicy::default()), 300);
+}
+
+#[test]
+fn max_of_hint_and_backoff_never_undercuts_backoff() {
+    let retry = RetryPolicy {
+        hint_mode: HintMode::MaxOfHintAndBackoff,
+        jitter_seed: None,
+    };
+
+    assert_eq!(rate_limited_delay(300, retry.clone()), 1500);
+    assert_eq!(rate_limited_delay(3000, retry), 3000);
+}
+
+#[test]
+fn hint_plus_jitter_is_bounded_and_deterministic() {
+    let retry = |seed| RetryPolicy {
+        hint_mode: HintMode::HintPlusJitter { max_jitter_ms: 500 },
+        jitter_seed: Some(seed),
+    };
+
+    let delays: Vec<u32> = (0..32)
+        .map(|seed| rate_limited_delay(3000, retry(seed)))
+        .collect();
+
+    assert!(delays.iter().all(|d| (3000..=3500).contains(d)));
+    assert!(
+        delays.iter().any(|d| *d != delays[0]),
+        "seeds must spread delays"
+    );
+    assert_eq!(
+        rate_limited_delay(3000, retry(7)),
+        rate_limited_delay(3000, retry(7))
+    );
+}
+
+#[test]
+fn combined_delay_is_still_clamped() {
+    let retry = RetryPolicy {
+        hint_mode: HintMode::HintPlusJitter {
+            max_jitter_ms: 10_000,
+        },
+        jitter_seed: Some(42),
+    };
+
+    assert_eq!(rate_limited_delay(u32::MAX, retry), 120_000);
+}
+
+/// The delay a client jittering within 500ms picks for a 3s hint.
+fn client_delay(client: &mut Client) -> u32 {
+    client.policy_mut().retry.hint_mode = HintMode::HintPlusJitter { max_jitter_ms: 500 };
+    match client.decide(
+        &ctx(1),
+        Outcome::RateLimited {
+            retry_after_ms: Some(3000),
+        },
+        None,
+        None,
+    ) {
+        Decision::Retry { after_ms, .. } => after_ms,
+        other => panic!("expected retry, got {:?}", other),
+    }
+}
+
+#[test]
+fn clients_draw_their_own_seeds_by_default() {
+    let delays: Vec<u32> = (0..32).map(|_| client_delay(&mut Client::new())).collect();
+    assert!(
+        delays.iter().any(|d| *d != delays[0]),
+        "clients must not return in lockstep: {delays:?}"
+    );
+
+    let fixed = || {
+        let mut client = Client::new();
+        client.policy_mut().retry.jitter_seed = Some(7);
+        client_delay(&mut client)
+    };
+    assert_eq!(fixed(), fixed());
+}
+
+#[test]
+fn a_drawn_seed_is_recorded_for_replay() {
+    let mut client = Client::new();
+    client.set_recording(true);
+    client_delay(&mut client);
+    client_delay(&mut client);
+
+    let entries = replay::parse(&client.take_recording()).unwrap();
+    assert!(replay::replay(&entries, None).is_identical());
+}
@@ -0,0 +1,108 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use serde_json::{json, Value};
+
+use transport_core::{
+    client::Client,
+    ffi::{tc_client_free, tc_client_new, tc_decide_json, tc_string_free},
+    json::decide_json,
+};
+
+fn call(input: Value) -> Value {
+    serde_json::from_str(&decide_json(&input.to_string())).unwrap()
+}
+
+fn get_401() -> Value {
+    json!({
+        "ctx": {
+            "method": "GET",
+            "attempt": 1,
+            "max_attempts": 3,
+            "idempotency_key": null,
+            "allow_non_idempotent_retry": false
+        },
+        "outcome": { "HttpStatus": 401 },
+        "auth_decision": "RefreshAndRetry"
+    })
+}
+
+#[test]
+fn decides_from_json() {
+    let out = call(json!({
+        "ctx": {
+            "method": "GET",
+            "attempt": 1,
+            "max_attempts": 3,
+            "idempotency_key": null,
+            "allow_non_idempotent_retry": false
+        },
+        "outcome": { "RateLimited": { "retry_after_ms": 3000 } }
+    }));
+
+    assert_eq!(
+        out["decision"],
+        json!({ "Retry": { "after_ms": 3000, "reason": "RateLimited" } })
+    );
+}
+
+#[test]
+fn stateless_calls_thread_auth_state_through_json() {
+    let first = call(get_401());
+    assert_eq!(
+        first["decision"],
+        json!({ "RefreshAndRetry": { "after_ms": 0 } })
+    );
+    assert_eq!(first["auth_state"]["refresh_attempted"], json!(true));
+
+    let mut again = get_401();
+    again["auth_state"] = first["auth_state"].clone();
+    let second = call(again);
+    assert_eq!(
+        second["decision"],
+        json!({ "Fail": { "reason": "AuthFailed", "retryable": false } })
+    );
+}
+
+#[test]
+fn client_keeps_state_between_json_calls() {
+    let mut client = Client::new();
+    let input = get_401().to_string();
+
+    let first: Value = serde_json::from_str(&client.decide_json(&input)).unwrap();
+    let second: Value = serde_json::from_str(&client.decide_json(&input)).unwrap();
+
+    assert!(first["decision"].get("RefreshAndRetry").is_some());
+    assert!(second["decision"].get("Fail").is_some());
+}
+
+#[test]
+fn malformed_input_yields_an_error_object() {
+    let out = call(json!({ "ctx": {}, "outcome": "NetworkError" }));
+
+    assert!(out["error"].is_string());
+    assert!(out.get("decision").is_none());
+}
+
+#[test]
+fn c_abi_round_trip() {
+    let client = tc_client_new();
+    let input = CString::new(get_401().to_string()).unwrap();
+    let mut output = ptr::null_mut();
+
+    assert_eq!(tc_decide_json(client, input.as_ptr(), &mut output), 0);
+    let out: Value =
+        serde_json::from_str(unsafe { CStr::from_ptr(output) }.to_str().unwrap()).unwrap();
+    assert!(out["decision"].get("RefreshAndRetry").is_some());
+    tc_string_free(output);
+
+    let bad = CString::new("not json").unwrap();
+    let mut output = ptr::null_mut();
+    assert_ne!(tc_decide_json(client, bad.as_ptr(), &mut output), 0);
+    assert!(!output.is_null());
+    tc_string_free(output);
+
+    assert_ne!(tc_decide_json(client, ptr::null(), &mut ptr::null_mut()), 0);
+
+    tc_client_free(client);
+}
@@ -0,0 +1,224 @@
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::ptr;
+
+use transport_core::{
+    auth::AuthDecision,
+    client::Client,
+    ffi::*,
+    metrics::{Counter, MetricsSnapshot},
+    model::{HttpMethod, Outcome, Priority, RequestContext},
+};
+
+fn ctx(attempt: u8, partition: Option<&str>) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: partition.map(str::to_string),
+        priority: Priority::Normal,
+    }
+}
+
+fn counter<'a>(
+    snapshot: &'a MetricsSnapshot,
+    name: &str,
+    labels: &[(&str, &str)],
+) -> Option<&'a Counter> {
+    snapshot.counters.iter().find(|c| {
+        c.name == name
+            && labels
+                .iter()
+                .all(|(k, v)| c.labels.get(*k).map(String::as_str) == Some(*v))
+    })
+}
+
+#[test]
+fn decisions_are_counted_by_kind_reason_and_partition() {
+    let mut client = Client::new();
+
+    client.decide(&ctx(1, None), Outcome::TimeoutError, None, None);
+    client.decide(&ctx(2, None), Outcome::TimeoutError, None, None);
+    client.decide(&ctx(3, None), Outcome::TimeoutError, None, None);
+    client.decide(&ctx(1, Some("api")), Outcome::Blocked, None, None);
+
+    let snapshot = client.metrics_snapshot();
+    let timeouts = [
+        ("partition", ""),
+        ("decision", "retry"),
+        ("reason", "timeout"),
+    ];
+    assert_eq!(
+        counter(&snapshot, "tc_decisions_total", &timeouts)
+            .unwrap()
+            .value,
+        2
+    );
+    let exhausted = [("partition", ""), ("reason", "max_attempts_exceeded")];
+    assert_eq!(
+        counter(&snapshot, "tc_decisions_total", &exhausted)
+            .unwrap()
+            .value,
+        1
+    );
+    let blocked = [("partition", "api"), ("reason", "hard_blocked")];
+    assert_eq!(
+        counter(&snapshot, "tc_decisions_total", &blocked)
+            .unwrap()
+            .value,
+        1
+    );
+
+    for attempt in ["1", "2"] {
+        let labels = [("partition", ""), ("attempt", attempt)];
+        assert_eq!(
+            counter(&snapshot, "tc_retries_total", &labels)
+                .unwrap()
+                .value,
+            1
+        );
+    }
+    assert!(counter(&snapshot, "tc_retries_total", &[("attempt", "3")]).is_none());
+
+    let delays = snapshot
+        .histograms
+        .iter()
+        .find(|h| h.labels["partition"].is_empty())
+        .unwrap();
+    assert_eq!(delays.count, 2);
+    assert_eq!(delays.buckets.last().unwrap().le, None);
+    assert_eq!(delays.buckets.last().unwrap().count, 2);
+}
+
+#[test]
+fn auth_refreshes_and_hint_clamps_are_counted() {
+    let mut client = Client::new();
+    let unauthorized = Outcome::HttpStatus(401);
+
+    client.decide(
+        &ctx(1, None),
+        unauthorized.clone(),
+        Some(AuthDecision::RefreshAndRetry),
+        None,
+    );
+    client.decide(
+        &ctx(1, None),
+        Outcome::RateLimited {
+            retry_after_ms: Some(600_000),
+        },
+        None,
+        None,
+    );
+
+    let snapshot = client.metrics_snapshot();
+    assert_eq!(
+        counter(
+            &snapshot,
+            "tc_auth_refreshes_total",
+            &[("result", "requested")]
+        )
+        .unwrap()
+        .value,
+        1
+    );
+    assert_eq!(
+        counter(&snapshot, "tc_retry_after_clamped_total", &[])
+            .unwrap()
+            .value,
+        1
+    );
+}
+
+#[test]
+fn reset_clears_metrics() {
+    let mut client = Client::new();
+    client.decide(&ctx(1, None), Outcome::TimeoutError, None, None);
+    client.reset();
+
+    let snapshot = client.metrics_snapshot();
+    assert!(snapshot.counters.is_empty());
+    assert!(snapshot.histograms.is_empty());
+}
+
+#[test]
+fn prometheus_text_exposition() {
+    let mut client = Client::new();
+    client.decide(&ctx(1, Some("a")), Outcome::TimeoutError, None, None);
+    client.decide(&ctx(1, Some("b")), Outcome::NetworkError, None, None);
+
+    let text = client.metrics_snapshot().to_prometheus();
+    assert!(text
+        .contains("tc_decisions_total{decision=\"retry\",partition=\"a\",reason=\"timeout\"} 1\n"));
+    assert!(text.contains("tc_retry_delay_ms_bucket{partition=\"b\",le=\"+Inf\"} 1\n"));
+    assert!(text.contains("tc_retry_delay_ms_count{partition=\"a\"} 1\n"));
+
+    // Each family is one block under a single TYPE line, across
+    // partitions.
+    let mut families: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if let Some(family) = line.strip_prefix("# TYPE ") {
+            let name = family.split(' ').next().unwrap();
+            assert!(!families.contains(&name), "duplicate TYPE for {name}");
+            families.push(name);
+            continue;
+        }
+        let current = *families.last().expect("samples follow a TYPE line");
+        assert!(line.starts_with(current), "{line} outside {current}");
+    }
+    assert_eq!(
+        families,
+        [
+            "tc_decisions_total",
+            "tc_retries_total",
+            "tc_retry_after_clamped_total",
+            "tc_retry_delay_ms",
+        ]
+    );
+}
+
+#[test]
+fn metrics_json_over_ffi() {
+    let client = tc_client_new();
+    let ctx = tc_request_context_t {
+        method: tc_http_method_t::TC_HTTP_GET as c_int,
+        attempt: 1,
+        max_attempts: 3,
+        allow_non_idempotent_retry: false,
+        idempotency_key: ptr::null(),
+        partition: ptr::null(),
+        priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+    };
+    let outcome = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_NETWORK_ERROR as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    };
+    tc_decide(
+        client,
+        &ctx,
+        &outcome,
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+
+    let json = tc_client_metrics_json(client);
+    assert!(!json.is_null());
+    let snapshot: MetricsSnapshot =
+        serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+    tc_string_free(json);
+
+    let labels = [("decision", "retry"), ("reason", "network_error")];
+    assert_eq!(
+        counter(&snapshot, "tc_decisions_total", &labels)
+            .unwrap()
+            .value,
+        1
+    );
+
+    assert!(tc_client_metrics_json(ptr::null()).is_null());
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+
+    tc_client_free(client);
+}
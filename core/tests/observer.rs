@@ -0,0 +1,151 @@
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_int;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use transport_core::{
+    client::Client,
+    ffi::*,
+    model::{Decision, HttpMethod, Outcome, Priority, RequestContext},
+    observer::{DecisionEvent, Observer},
+};
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(u8, Decision, Option<u32>)>>);
+
+impl Observer for Recorder {
+    fn on_decision(&self, event: &DecisionEvent<'_>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((event.ctx.attempt, event.decision.clone(), event.timeout_ms));
+    }
+}
+
+fn ctx(attempt: u8) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt,
+        max_attempts: 2,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: Some("api".to_string()),
+        priority: Priority::Normal,
+    }
+}
+
+#[test]
+fn observer_sees_every_decision() {
+    let recorder = Arc::new(Recorder::default());
+    let mut client = Client::new();
+    client.set_observer(recorder.clone());
+
+    client.decide(&ctx(1), Outcome::TimeoutError, None, None);
+    client.decide_json(
+        r#"{"ctx": {"method": "GET", "attempt": 2, "max_attempts": 2,
+            "idempotency_key": null, "allow_non_idempotent_retry": false},
+            "outcome": "TimeoutError"}"#,
+    );
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, 1);
+    assert!(matches!(events[0].1, Decision::Retry { .. }));
+    assert!(events[0].2.is_some());
+    assert!(matches!(events[1].1, Decision::Fail { .. }));
+    assert_eq!(events[1].2, None);
+}
+
+#[test]
+fn cleared_observer_is_not_called() {
+    let recorder = Arc::new(Recorder::default());
+    let mut client = Client::new();
+    client.set_observer(recorder.clone());
+    client.clear_observer();
+
+    client.decide(&ctx(1), Outcome::NetworkError, None, None);
+
+    assert!(recorder.0.lock().unwrap().is_empty());
+}
+
+#[derive(Default)]
+struct Seen {
+    calls: u32,
+    decision: Option<tc_decision_t>,
+    retry_reason: u8,
+    outcome_kind: c_int,
+    partition: Option<String>,
+}
+
+extern "C" fn on_decision(userdata: *mut c_void, event: *const tc_decision_event_t) {
+    let seen = unsafe { &mut *(userdata as *mut Seen) };
+    let event = unsafe { &*event };
+
+    seen.calls += 1;
+    seen.decision = Some(event.decision);
+    seen.retry_reason = event.retry_reason;
+    seen.outcome_kind = event.outcome_kind;
+    seen.partition = (!event.partition.is_null()).then(|| {
+        unsafe { CStr::from_ptr(event.partition) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    });
+}
+
+#[test]
+fn c_callback_receives_the_event() {
+    let client = tc_client_new();
+    let mut seen = Seen::default();
+    tc_client_set_observer(
+        client,
+        Some(on_decision),
+        &mut seen as *mut Seen as *mut c_void,
+    );
+
+    let partition = CString::new("search").unwrap();
+    let ctx = tc_request_context_t {
+        method: tc_http_method_t::TC_HTTP_GET as c_int,
+        attempt: 1,
+        max_attempts: 3,
+        allow_non_idempotent_retry: false,
+        idempotency_key: ptr::null(),
+        partition: partition.as_ptr(),
+        priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+    };
+    let outcome = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_RATE_LIMITED as c_int,
+        http_status: 0,
+        retry_after_ms: 2_000,
+    };
+    tc_decide(
+        client,
+        &ctx,
+        &outcome,
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+
+    tc_client_set_observer(client, None, ptr::null_mut());
+    tc_decide(
+        client,
+        &ctx,
+        &outcome,
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+
+    assert_eq!(seen.calls, 1);
+    assert_eq!(seen.decision, Some(tc_decision_t::TC_DECISION_RETRY));
+    assert_eq!(
+        seen.retry_reason,
+        tc_retry_reason_t::TC_RETRY_REASON_RATE_LIMITED as u8
+    );
+    assert_eq!(
+        seen.outcome_kind,
+        tc_outcome_kind_t::TC_OUTCOME_RATE_LIMITED as c_int
+    );
+    assert_eq!(seen.partition.as_deref(), Some("search"));
+
+    tc_client_free(client);
+}
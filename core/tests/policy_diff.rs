@@ -0,0 +1,100 @@
+use transport_core::{
+    model::{Decision, Outcome, RetryReason},
+    policy::{diff_policies, InputSpace, Policy},
+    retry::HintMode,
+};
+
+fn with_hint_mode(hint_mode: HintMode) -> Policy {
+    let mut policy = Policy::default();
+    policy.retry.hint_mode = hint_mode;
+    policy
+}
+
+#[test]
+fn identical_policies_have_no_differences() {
+    let diff = diff_policies(
+        &Policy::default(),
+        &Policy::default(),
+        &InputSpace::default(),
+    );
+
+    // 6 methods x 4 idempotency variants x 3 attempts, each with 25
+    // outcomes that ignore auth plus 401 under 18 auth inputs.
+    assert_eq!(diff.inputs, 6 * 4 * 3 * (25 + 18));
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn max_of_hint_and_backoff_only_raises_short_hints() {
+    let diff = diff_policies(
+        &Policy::default(),
+        &with_hint_mode(HintMode::MaxOfHintAndBackoff),
+        &InputSpace::default(),
+    );
+
+    // 21 retryable method/idempotency variants (POST only with key and
+    // override) x 2 attempts with retries left x hints 0, 1, 500, 1499.
+    assert_eq!(diff.differences.len(), 21 * 2 * 4);
+    for difference in &diff.differences {
+        let Outcome::RateLimited {
+            retry_after_ms: Some(hint),
+        } = difference.input.outcome
+        else {
+            panic!("unexpected difference {difference:?}");
+        };
+        assert!(hint < 1_500);
+        assert_eq!(
+            difference.old,
+            Decision::Retry {
+                after_ms: hint,
+                reason: RetryReason::RateLimited
+            }
+        );
+        assert_eq!(
+            difference.new,
+            Decision::Retry {
+                after_ms: 1_500,
+                reason: RetryReason::RateLimited
+            }
+        );
+    }
+}
+
+#[test]
+fn jitter_stays_within_its_bound() {
+    let diff = diff_policies(
+        &Policy::default(),
+        &with_hint_mode(HintMode::HintPlusJitter { max_jitter_ms: 250 }),
+        &InputSpace::default(),
+    );
+
+    assert!(!diff.is_empty());
+    for difference in &diff.differences {
+        let (Decision::Retry { after_ms: old, .. }, Decision::Retry { after_ms: new, .. }) =
+            (&difference.old, &difference.new)
+        else {
+            panic!("unexpected difference {difference:?}");
+        };
+        assert!(new > old && *new <= old + 250, "{old} -> {new}");
+        assert!(*new <= 120_000);
+    }
+}
+
+#[test]
+fn the_space_is_configurable() {
+    let space = InputSpace {
+        methods: vec![transport_core::model::HttpMethod::POST],
+        max_attempts: 1,
+        statuses: vec![],
+        retry_after_ms: vec![100],
+    };
+
+    // Every attempt is the last, so no policy can make these retry.
+    let diff = diff_policies(
+        &Policy::default(),
+        &with_hint_mode(HintMode::MaxOfHintAndBackoff),
+        &space,
+    );
+    assert_eq!(diff.inputs, 4 * 6);
+    assert!(diff.is_empty());
+}
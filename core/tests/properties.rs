@@ -0,0 +1,322 @@
+//! Invariants of `decide` over generated inputs and policies. New policy
+//! knobs that affect decisions belong in the `policy()` strategy.
+
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+
+use transport_core::{
+    auth::{AuthDecision, AuthState},
+    client::Client,
+    decision::{decide_with_policy, shed_retry},
+    model::{Decision, FailReason, HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+    retry::{clamp_retry_after, is_idempotent, HintMode},
+};
+
+const MAX_RETRY_AFTER_MS: u32 = 120_000;
+
+fn method() -> impl Strategy<Value = HttpMethod> {
+    prop_oneof![
+        Just(HttpMethod::GET),
+        Just(HttpMethod::POST),
+        Just(HttpMethod::PUT),
+        Just(HttpMethod::DELETE),
+        Just(HttpMethod::HEAD),
+        Just(HttpMethod::OPTIONS),
+    ]
+}
+
+fn priority() -> impl Strategy<Value = Priority> {
+    prop_oneof![
+        Just(Priority::Background),
+        Just(Priority::Normal),
+        Just(Priority::Critical),
+    ]
+}
+
+fn ctx() -> impl Strategy<Value = RequestContext> {
+    (
+        method(),
+        0..=8u8,
+        0..=8u8,
+        proptest::option::of("[a-z0-9-]{0,12}"),
+        any::<bool>(),
+        priority(),
+    )
+        .prop_map(
+            |(method, attempt, max_attempts, idempotency_key, allow, priority)| RequestContext {
+                method,
+                attempt,
+                max_attempts,
+                idempotency_key,
+                allow_non_idempotent_retry: allow,
+                partition: None,
+                priority,
+            },
+        )
+}
+
+fn retry_after_ms() -> impl Strategy<Value = u32> {
+    prop_oneof![
+        0..=2_000u32,
+        MAX_RETRY_AFTER_MS - 10..=MAX_RETRY_AFTER_MS + 10,
+        any::<u32>(),
+    ]
+}
+
+fn outcome() -> impl Strategy<Value = Outcome> {
+    prop_oneof![
+        Just(Outcome::NetworkError),
+        Just(Outcome::TimeoutError),
+        proptest::option::of(retry_after_ms())
+            .prop_map(|retry_after_ms| Outcome::RateLimited { retry_after_ms }),
+        Just(Outcome::Blocked),
+        Just(Outcome::Captcha),
+        prop_oneof![Just(401u16), Just(429u16), 100..=599u16, any::<u16>()]
+            .prop_map(Outcome::HttpStatus),
+    ]
+}
+
+fn auth_state() -> impl Strategy<Value = AuthState> {
+    (any::<bool>(), any::<bool>()).prop_map(|(refresh_in_progress, refresh_attempted)| AuthState {
+        refresh_in_progress,
+        refresh_attempted,
+    })
+}
+
+fn auth_decision() -> impl Strategy<Value = Option<AuthDecision>> {
+    proptest::option::of(prop_oneof![
+        Just(AuthDecision::RefreshAndRetry),
+        Just(AuthDecision::Fail),
+    ])
+}
+
+fn policy() -> impl Strategy<Value = Policy> {
+    let hint_mode = prop_oneof![
+        Just(HintMode::HintOnly),
+        Just(HintMode::MaxOfHintAndBackoff),
+        prop_oneof![0..=1_000u32, any::<u32>()]
+            .prop_map(|max_jitter_ms| HintMode::HintPlusJitter { max_jitter_ms }),
+    ];
+
+    (hint_mode, any::<u64>()).prop_map(|(hint_mode, jitter_seed)| {
+        let mut policy = Policy::default();
+        policy.retry.hint_mode = hint_mode;
+        policy.retry.jitter_seed = Some(jitter_seed);
+        policy
+    })
+}
+
+/// One generated `decide` call.
+#[derive(Debug, Clone)]
+struct Input {
+    ctx: RequestContext,
+    outcome: Outcome,
+    auth_decision: Option<AuthDecision>,
+    auth_state: AuthState,
+    refresh_result: Option<bool>,
+    policy: Policy,
+}
+
+impl Input {
+    /// The decision and the auth state it leaves behind.
+    fn decide(&self) -> (Decision, AuthState) {
+        let mut auth_state = self.auth_state.clone();
+        let decision = decide_with_policy(
+            &self.ctx,
+            self.outcome.clone(),
+            self.auth_decision,
+            &mut auth_state,
+            self.refresh_result,
+            &self.policy,
+        );
+        (decision, auth_state)
+    }
+}
+
+fn input() -> impl Strategy<Value = Input> {
+    (
+        ctx(),
+        outcome(),
+        auth_decision(),
+        auth_state(),
+        proptest::option::of(any::<bool>()),
+        policy(),
+    )
+        .prop_map(
+            |(ctx, outcome, auth_decision, auth_state, refresh_result, policy)| Input {
+                ctx,
+                outcome,
+                auth_decision,
+                auth_state,
+                refresh_result,
+                policy,
+            },
+        )
+}
+
+fn may_retry(ctx: &RequestContext) -> bool {
+    ctx.attempt < ctx.max_attempts
+        && (is_idempotent(&ctx.method)
+            || (ctx.idempotency_key.is_some() && ctx.allow_non_idempotent_retry))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 1_000,
+        failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn never_retries_without_attempts_left(mut input in input(), max_attempts in 0..=8u8) {
+        input.ctx.max_attempts = max_attempts.min(input.ctx.attempt);
+
+        let (decision, _) = input.decide();
+        prop_assert!(!matches!(decision, Decision::Retry { .. }), "{decision:?}");
+        // Only the refresh itself may be requested; retrying after it may not.
+        if let Decision::RefreshAndRetry { after_ms } = decision {
+            prop_assert_eq!(input.refresh_result, None);
+            prop_assert_eq!(after_ms, 0);
+        }
+    }
+
+    #[test]
+    fn non_idempotent_requests_without_key_and_override_never_retry(mut input in input()) {
+        input.ctx.method = HttpMethod::POST;
+        if input.ctx.idempotency_key.is_some() {
+            input.ctx.allow_non_idempotent_retry = false;
+        }
+
+        let (decision, _) = input.decide();
+        prop_assert!(!matches!(decision, Decision::Retry { .. }), "{decision:?}");
+        if let Decision::RefreshAndRetry { .. } = decision {
+            prop_assert_eq!(input.refresh_result, None);
+        }
+    }
+
+    #[test]
+    fn blocked_and_captcha_always_fail_hard(
+        input in input(),
+        captcha in any::<bool>(),
+    ) {
+        let input = Input {
+            outcome: if captcha { Outcome::Captcha } else { Outcome::Blocked },
+            ..input
+        };
+
+        let (decision, auth_state) = input.decide();
+        prop_assert_eq!(
+            decision,
+            Decision::Fail { reason: FailReason::HardBlocked, retryable: false }
+        );
+        prop_assert_eq!(auth_state, input.auth_state);
+    }
+
+    #[test]
+    fn delays_never_exceed_the_clamp(input in input()) {
+        match input.decide().0 {
+            Decision::Retry { after_ms, .. } | Decision::RefreshAndRetry { after_ms } => {
+                prop_assert!(after_ms <= MAX_RETRY_AFTER_MS, "{after_ms}");
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn retry_after_hints_are_honoured(input in input(), hint in retry_after_ms()) {
+        let input = Input {
+            outcome: Outcome::RateLimited { retry_after_ms: Some(hint) },
+            ..input
+        };
+
+        match input.decide().0 {
+            Decision::Retry { after_ms, .. } => {
+                prop_assert!(after_ms >= clamp_retry_after(hint), "{after_ms} < {hint}");
+                if input.policy.retry.hint_mode == HintMode::HintOnly {
+                    prop_assert_eq!(after_ms, clamp_retry_after(hint));
+                }
+            }
+            decision => prop_assert!(!may_retry(&input.ctx), "{decision:?}"),
+        }
+    }
+
+    #[test]
+    fn decide_never_proceeds_or_fails_retryably(input in input()) {
+        let decision = input.decide().0;
+        prop_assert!(!matches!(
+            decision,
+            Decision::Proceed | Decision::Fail { retryable: true, .. }
+        ), "{decision:?}");
+    }
+
+    #[test]
+    fn decisions_are_deterministic(input in input()) {
+        prop_assert_eq!(input.decide(), input.decide());
+    }
+
+    #[test]
+    fn only_auth_errors_touch_auth_state(input in input()) {
+        if input.outcome != Outcome::HttpStatus(401) {
+            prop_assert_eq!(input.decide().1, input.auth_state);
+        }
+    }
+
+    #[test]
+    fn a_request_refreshes_at_most_once(input in input()) {
+        let input = Input {
+            outcome: Outcome::HttpStatus(401),
+            auth_decision: Some(AuthDecision::RefreshAndRetry),
+            ..input
+        };
+
+        let (first, auth_state) = input.decide();
+        if matches!(first, Decision::RefreshAndRetry { .. }) {
+            prop_assert!(!input.auth_state.refresh_attempted);
+        }
+        prop_assert!(auth_state.refresh_attempted);
+
+        let again = Input { auth_state, ..input };
+        prop_assert_eq!(
+            again.decide().0,
+            Decision::Fail { reason: FailReason::AuthFailed, retryable: false }
+        );
+    }
+
+    #[test]
+    fn shedding_spares_critical_requests_and_non_retries(
+        input in input(),
+        overloaded in any::<bool>(),
+    ) {
+        let decision = input.decide().0;
+        let shed = shed_retry(&input.ctx, decision.clone(), overloaded);
+
+        let sheddable = overloaded
+            && input.ctx.priority != Priority::Critical
+            && matches!(decision, Decision::Retry { .. });
+        if sheddable {
+            prop_assert_eq!(shed, Decision::Fail { reason: FailReason::Shed, retryable: true });
+        } else {
+            prop_assert_eq!(shed, decision);
+        }
+    }
+
+    #[test]
+    fn client_matches_the_free_function(input in input()) {
+        let mut client = Client::with_policy(input.policy.clone());
+        if input.auth_state != AuthState::new() {
+            let mut state = client.export_state(0);
+            state.auth = input.auth_state.clone();
+            client.import_state(state).unwrap();
+        }
+
+        let (expected, auth_state) = input.decide();
+        let decision = client.decide(
+            &input.ctx,
+            input.outcome.clone(),
+            input.auth_decision,
+            input.refresh_result,
+        );
+        prop_assert_eq!(decision, expected);
+        prop_assert_eq!(client.auth_state(), &auth_state);
+    }
+}
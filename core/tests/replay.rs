@@ -0,0 +1,250 @@
+use std::ffi::CStr;
+use std::os::raw::c_int;
+use std::ptr;
+
+use transport_core::{
+    auth::AuthDecision,
+    client::Client,
+    concurrency::ConcurrencyPolicy,
+    ffi::*,
+    model::{Decision, FailReason, HttpMethod, Outcome, Priority, RequestContext, RetryReason},
+    policy::Policy,
+    replay::{self, Entry, RecordingError, RECORDING_VERSION},
+    retry::HintMode,
+};
+
+fn ctx(attempt: u8, priority: Priority) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority,
+    }
+}
+
+fn rate_limited(retry_after_ms: Option<u32>) -> Outcome {
+    Outcome::RateLimited { retry_after_ms }
+}
+
+/// Drives a recording client through auth refreshes, resets, a policy
+/// change and load shedding, and returns the log.
+fn record_session() -> String {
+    let mut client = Client::new();
+    client.set_concurrency_policy(ConcurrencyPolicy {
+        initial_limit: 1,
+        ..ConcurrencyPolicy::default()
+    });
+    client.set_recording(true);
+
+    let normal = Priority::Normal;
+    client.decide(
+        &ctx(1, normal),
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        None,
+    );
+    client.decide(
+        &ctx(2, normal),
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        Some(false),
+    );
+    client.reset_auth();
+    client.decide(
+        &ctx(1, normal),
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        None,
+    );
+    client.decide(&ctx(1, normal), rate_limited(Some(2_000)), None, None);
+
+    client.policy_mut().retry.hint_mode = HintMode::MaxOfHintAndBackoff;
+    client.decide(&ctx(1, normal), rate_limited(Some(100)), None, None);
+
+    client.acquire(false, normal);
+    client.acquire(false, normal);
+    let shed = client.decide(
+        &ctx(1, Priority::Background),
+        Outcome::TimeoutError,
+        None,
+        None,
+    );
+    assert!(matches!(
+        shed,
+        Decision::Fail {
+            reason: FailReason::Shed,
+            ..
+        }
+    ));
+
+    client.take_recording()
+}
+
+#[test]
+fn a_recorded_session_replays_identically() {
+    let log = record_session();
+    let entries = replay::parse(&log).unwrap();
+
+    let kinds: Vec<&str> = entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::Start { .. } => "start",
+            Entry::Decide(_) => "decide",
+            Entry::Policy { .. } => "policy",
+            Entry::Auth { .. } => "auth",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        ["start", "decide", "decide", "auth", "decide", "decide", "policy", "decide", "decide"]
+    );
+
+    let report = replay::replay(&entries, None);
+    assert_eq!(report.decisions, 6);
+    assert_eq!(report.engine_versions, [env!("CARGO_PKG_VERSION")]);
+    assert!(report.is_identical(), "{:?}", report.differences);
+}
+
+#[test]
+fn a_changed_decision_is_reported() {
+    let log = record_session().replace(
+        r#""decision":{"Fail":{"reason":"AuthFailed","retryable":false}}"#,
+        r#""decision":{"Fail":{"reason":"Unknown","retryable":false}}"#,
+    );
+
+    let report = replay::replay(&replay::parse(&log).unwrap(), None);
+    assert_eq!(report.differences.len(), 1);
+
+    let difference = &report.differences[0];
+    assert_eq!(difference.entry, 2);
+    assert_eq!(difference.outcome, Outcome::HttpStatus(401));
+    assert_eq!(
+        difference.replayed,
+        Decision::Fail {
+            reason: FailReason::AuthFailed,
+            retryable: false
+        }
+    );
+}
+
+#[test]
+fn replaying_under_another_policy_shows_its_effect() {
+    let entries = replay::parse(&record_session()).unwrap();
+
+    let report = replay::replay(&entries, Some(&Policy::default()));
+
+    // Only the short hint after the recorded hint mode change is affected.
+    assert_eq!(report.differences.len(), 1);
+    let difference = &report.differences[0];
+    assert_eq!(difference.outcome, rate_limited(Some(100)));
+    assert_eq!(
+        difference.replayed,
+        Decision::Retry {
+            after_ms: 100,
+            reason: RetryReason::RateLimited
+        }
+    );
+}
+
+#[test]
+fn chunks_concatenate_into_one_log() {
+    let mut client = Client::new();
+    client.set_recording(true);
+    client.decide(&ctx(1, Priority::Normal), Outcome::TimeoutError, None, None);
+    let mut log = client.take_recording();
+
+    client.decide(&ctx(2, Priority::Normal), Outcome::TimeoutError, None, None);
+    client.set_recording(false);
+    client.decide(&ctx(3, Priority::Normal), Outcome::TimeoutError, None, None);
+    log.push_str(&client.take_recording());
+    assert_eq!(client.take_recording(), "");
+
+    // Restarting writes a fresh start line.
+    client.set_recording(true);
+    client.decide(&ctx(1, Priority::Normal), Outcome::Blocked, None, None);
+    log.push_str(&client.take_recording());
+
+    let entries = replay::parse(&log).unwrap();
+    assert_eq!(entries.len(), 5);
+    let report = replay::replay(&entries, None);
+    assert_eq!(report.decisions, 3);
+    assert!(report.is_identical());
+}
+
+#[test]
+fn malformed_logs_are_rejected() {
+    assert_eq!(replay::parse("").unwrap_err(), RecordingError::MissingStart);
+
+    let auth_first =
+        r#"{"event":"auth","auth":{"refresh_in_progress":false,"refresh_attempted":false}}"#;
+    assert_eq!(
+        replay::parse(auth_first).unwrap_err(),
+        RecordingError::MissingStart
+    );
+
+    let log = record_session();
+    let newer = log.replacen(
+        &format!(r#""version":{RECORDING_VERSION}"#),
+        r#""version":99"#,
+        1,
+    );
+    assert_eq!(
+        replay::parse(&newer).unwrap_err(),
+        RecordingError::UnsupportedVersion {
+            line: 1,
+            version: 99
+        }
+    );
+
+    let truncated = format!("{log}{{\"event\":\"decide\"");
+    assert!(matches!(
+        replay::parse(&truncated),
+        Err(RecordingError::Malformed { line: 10, .. })
+    ));
+}
+
+#[test]
+fn ffi_recording() {
+    let client = tc_client_new();
+    tc_client_set_recording(client, true);
+
+    let ctx = tc_request_context_t {
+        method: tc_http_method_t::TC_HTTP_GET as c_int,
+        attempt: 1,
+        max_attempts: 3,
+        allow_non_idempotent_retry: false,
+        idempotency_key: ptr::null(),
+        partition: ptr::null(),
+        priority: tc_priority_t::TC_PRIORITY_NORMAL as c_int,
+    };
+    let outcome = tc_outcome_t {
+        kind: tc_outcome_kind_t::TC_OUTCOME_TIMEOUT_ERROR as c_int,
+        http_status: 0,
+        retry_after_ms: 0,
+    };
+    tc_decide(
+        client,
+        &ctx,
+        &outcome,
+        tc_auth_decision_t::TC_AUTH_FAIL as c_int,
+        -1,
+    );
+
+    let log = tc_client_take_recording(client);
+    let text = unsafe { CStr::from_ptr(log) }.to_str().unwrap().to_string();
+    tc_string_free(log);
+
+    let report = replay::replay(&replay::parse(&text).unwrap(), None);
+    assert_eq!(report.decisions, 1);
+    assert!(report.is_identical());
+
+    let empty = tc_client_take_recording(client);
+    assert_eq!(unsafe { CStr::from_ptr(empty) }.to_bytes(), b"");
+    tc_string_free(empty);
+
+    assert!(tc_client_take_recording(ptr::null_mut()).is_null());
+    tc_client_free(client);
+}
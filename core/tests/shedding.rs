@@ -0,0 +1,175 @@
+use transport_core::{
+    auth::AuthState,
+    client::Client,
+    concurrency::{Admission, ConcurrencyPolicy},
+    decision::decide,
+    model::{Decision, FailReason, HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+};
+
+fn client() -> Client {
+    let mut client = Client::new();
+    client.set_concurrency_policy(ConcurrencyPolicy {
+        initial_limit: 4,
+        max_queue: 1,
+        background_share_percent: 50,
+        ..ConcurrencyPolicy::default()
+    });
+    client
+}
+
+fn ctx(priority: Priority) -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: None,
+        allow_non_idempotent_retry: false,
+        partition: None,
+        priority,
+    }
+}
+
+#[test]
+fn background_requests_only_get_their_share_of_the_limit() {
+    let mut client = client();
+
+    assert_eq!(
+        client.acquire(false, Priority::Background),
+        Admission::Acquired
+    );
+    assert_eq!(
+        client.acquire(false, Priority::Background),
+        Admission::Acquired
+    );
+    assert_eq!(
+        client.acquire(false, Priority::Background),
+        Admission::Rejected
+    );
+
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+}
+
+#[test]
+fn critical_requests_queue_past_max_queue() {
+    let mut client = client();
+
+    for _ in 0..4 {
+        client.acquire(false, Priority::Normal);
+    }
+
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Queued);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Rejected);
+    assert_eq!(client.acquire(false, Priority::Critical), Admission::Queued);
+}
+
+#[test]
+fn critical_queueing_is_bounded() {
+    let mut client = client();
+
+    for _ in 0..4 {
+        client.acquire(false, Priority::Normal);
+    }
+
+    // `max_queue` plus one limit's worth.
+    for _ in 0..5 {
+        assert_eq!(client.acquire(false, Priority::Critical), Admission::Queued);
+    }
+    assert_eq!(
+        client.acquire(false, Priority::Critical),
+        Admission::Rejected
+    );
+}
+
+#[test]
+fn retries_are_shed_by_priority_under_load() {
+    let mut client = client();
+
+    for _ in 0..2 {
+        client.acquire(false, Priority::Normal);
+    }
+
+    assert_eq!(
+        client.decide(
+            &ctx(Priority::Background),
+            Outcome::NetworkError,
+            None,
+            None
+        ),
+        Decision::Fail {
+            reason: FailReason::Shed,
+            retryable: true,
+        }
+    );
+    assert!(matches!(
+        client.decide(&ctx(Priority::Normal), Outcome::NetworkError, None, None),
+        Decision::Retry { .. }
+    ));
+
+    for _ in 0..3 {
+        client.acquire(false, Priority::Normal);
+    }
+
+    assert!(matches!(
+        client.decide(&ctx(Priority::Normal), Outcome::NetworkError, None, None),
+        Decision::Fail {
+            reason: FailReason::Shed,
+            ..
+        }
+    ));
+    assert!(matches!(
+        client.decide(&ctx(Priority::Critical), Outcome::NetworkError, None, None),
+        Decision::Retry { .. }
+    ));
+}
+
+#[test]
+fn shedding_does_not_change_non_retry_decisions() {
+    let mut client = client();
+
+    for _ in 0..4 {
+        client.acquire(false, Priority::Critical);
+    }
+
+    assert_eq!(
+        client.decide(&ctx(Priority::Background), Outcome::Blocked, None, None),
+        Decision::Fail {
+            reason: FailReason::HardBlocked,
+            retryable: false,
+        }
+    );
+}
+
+#[test]
+fn background_share_of_an_extreme_limit_does_not_overflow() {
+    let policy: Policy = serde_json::from_str(
+        r#"{"concurrency":{"initial_limit":4294967295,"max_limit":4294967295}}"#,
+    )
+    .unwrap();
+    let mut client = Client::with_policy(policy);
+
+    assert_eq!(
+        client.acquire(false, Priority::Background),
+        Admission::Acquired
+    );
+    assert!(!client
+        .concurrency()
+        .would_reject(&client.policy().concurrency, Priority::Background));
+}
+
+#[test]
+fn stateless_decisions_ignore_priority() {
+    let decide = |priority| {
+        decide(
+            &ctx(priority),
+            Outcome::NetworkError,
+            None,
+            &mut AuthState::new(),
+            None,
+        )
+    };
+
+    assert_eq!(decide(Priority::Background), decide(Priority::Critical));
+    assert!(Priority::Background < Priority::Normal && Priority::Normal < Priority::Critical);
+}
@@ -0,0 +1,152 @@
+use transport_core::{
+    concurrency::ConcurrencyPolicy,
+    policy::Policy,
+    simulation::{simulate, SimulationConfig, Upstream},
+};
+
+#[test]
+fn healthy_upstream_needs_no_retries() {
+    let report = simulate(&SimulationConfig::default());
+
+    assert_eq!(report.requests, 1_000);
+    assert_eq!(report.attempts, 1_000);
+    assert_eq!(report.amplification, 1.0);
+    assert_eq!(report.success_rate, 1.0);
+    assert_eq!(report.success_latency.p50_ms, 50);
+    assert_eq!(report.success_latency.max_ms, 50);
+}
+
+#[test]
+fn retries_mask_random_failures() {
+    let config = SimulationConfig {
+        upstream: Upstream {
+            failure_percent: 20,
+            latency_jitter_ms: 20,
+            ..Upstream::default()
+        },
+        seed: 7,
+        ..SimulationConfig::default()
+    };
+
+    let report = simulate(&config);
+    assert!(report.amplification > 1.1, "{report:?}");
+    assert!(report.success_rate > 0.97, "{report:?}");
+    assert_eq!(
+        report.failures.get("MaxAttemptsExceeded").copied(),
+        Some(1_000 - report.succeeded)
+    );
+    // Failed first attempts wait 200 ms before retrying.
+    assert!(report.success_latency.p99_ms >= 250, "{report:?}");
+
+    // Same seed, same run.
+    assert_eq!(simulate(&config), report);
+}
+
+#[test]
+fn retries_amplify_load_on_a_rate_limited_upstream() {
+    let limited = |max_attempts| SimulationConfig {
+        upstream: Upstream {
+            rate_limit_per_sec: Some(50),
+            ..Upstream::default()
+        },
+        max_attempts,
+        ..SimulationConfig::default()
+    };
+
+    let single = simulate(&limited(1));
+    let retrying = simulate(&limited(5));
+
+    assert_eq!(single.amplification, 1.0);
+    assert!(
+        retrying.amplification > single.amplification,
+        "{retrying:?}"
+    );
+    assert!(retrying.outcomes["RateLimited"] > single.outcomes["RateLimited"]);
+    assert!(retrying.success_rate > single.success_rate);
+}
+
+#[test]
+fn overload_times_out() {
+    let report = simulate(&SimulationConfig {
+        upstream: Upstream {
+            capacity: 2,
+            latency_ms: 500,
+            ..Upstream::default()
+        },
+        clients: 20,
+        requests_per_client: 5,
+        ..SimulationConfig::default()
+    });
+
+    assert!(report.outcomes["TimeoutError"] > 0, "{report:?}");
+    assert!(report.success_rate < 1.0);
+    assert!(report.latency.max_ms >= 1_000);
+}
+
+#[test]
+fn concurrency_policies_shape_the_run() {
+    let config = |concurrency| SimulationConfig {
+        upstream: Upstream {
+            capacity: 2,
+            latency_ms: 500,
+            ..Upstream::default()
+        },
+        clients: 20,
+        requests_per_client: 5,
+        policy: Policy {
+            concurrency,
+            ..Policy::default()
+        },
+        ..SimulationConfig::default()
+    };
+    let one_at_a_time = ConcurrencyPolicy {
+        initial_limit: 1,
+        min_limit: 1,
+        max_limit: 1,
+        ..ConcurrencyPolicy::default()
+    };
+
+    let open = simulate(&config(ConcurrencyPolicy::default()));
+    assert_eq!(open.queued, 0, "{open:?}");
+    assert!(!open.failures.contains_key("Rejected"), "{open:?}");
+
+    // Without a queue, requests beyond the limit are shed before they
+    // reach the upstream.
+    let shedding = simulate(&config(one_at_a_time.clone()));
+    assert!(shedding.failures["Rejected"] > 0, "{shedding:?}");
+    assert!(shedding.attempts < open.attempts, "{shedding:?}");
+
+    // With one, they wait for a slot instead.
+    let queueing = simulate(&config(ConcurrencyPolicy {
+        max_queue: 100,
+        ..one_at_a_time
+    }));
+    assert!(queueing.queued > 0, "{queueing:?}");
+    assert!(!queueing.failures.contains_key("Rejected"), "{queueing:?}");
+    assert!(
+        queueing.latency.max_ms > open.latency.max_ms,
+        "{queueing:?}"
+    );
+}
+
+#[test]
+fn extreme_latencies_do_not_overflow() {
+    let config = SimulationConfig {
+        upstream: Upstream {
+            latency_ms: u32::MAX,
+            latency_jitter_ms: u32::MAX,
+            ..Upstream::default()
+        },
+        clients: 1,
+        requests_per_client: 20,
+        seed: 3,
+        ..SimulationConfig::default()
+    };
+
+    let report = simulate(&config);
+    assert_eq!(report.success_rate, 1.0);
+    assert!(
+        report.success_latency.max_ms > u64::from(u32::MAX),
+        "{report:?}"
+    );
+}
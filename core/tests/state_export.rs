@@ -0,0 +1,179 @@
+use std::ffi::{CStr, CString};
+
+use serde_json::Value;
+
+use transport_core::{
+    auth::AuthDecision,
+    client::{Client, ExportedState, StateError, STATE_VERSION},
+    concurrency::{Admission, ConcurrencyPolicy},
+    ffi::{
+        tc_client_export_state, tc_client_free, tc_client_import_state, tc_client_new,
+        tc_last_status, tc_recommended_timeout_ms, tc_status_t, tc_string_free,
+    },
+    model::{HttpMethod, Outcome, Priority, RequestContext},
+    policy::Policy,
+    stats::TimeoutPolicy,
+};
+
+fn warmed_up() -> Client {
+    let mut client = Client::new();
+
+    for latency in 1..=50 {
+        client.record_attempt(Some("search"), latency * 10, None);
+    }
+    client.report_hedge_result(1);
+    client.acquire(false, Priority::Normal);
+    client.release(80, Some(&Outcome::TimeoutError));
+    client.decide(
+        &RequestContext {
+            method: HttpMethod::GET,
+            attempt: 1,
+            max_attempts: 3,
+            idempotency_key: None,
+            allow_non_idempotent_retry: false,
+            partition: None,
+            priority: Priority::Normal,
+        },
+        Outcome::HttpStatus(401),
+        Some(AuthDecision::RefreshAndRetry),
+        None,
+    );
+    client
+}
+
+#[test]
+fn round_trip_restores_learned_state() {
+    let original = warmed_up();
+    let document = original.export_state(1_700_000_000_000).to_json();
+
+    let mut restored = Client::new();
+    restored
+        .import_state(ExportedState::from_json(&document).unwrap())
+        .unwrap();
+
+    assert_eq!(restored.auth_state(), original.auth_state());
+    assert_eq!(restored.latency_stats(), original.latency_stats());
+    assert_eq!(restored.hedge_stats(), original.hedge_stats());
+    assert_eq!(
+        restored.concurrency().limit(),
+        original.concurrency().limit()
+    );
+    assert_eq!(
+        restored.recommended_timeout_ms(Some("search")),
+        original.recommended_timeout_ms(Some("search"))
+    );
+}
+
+#[test]
+fn import_keeps_local_slots() {
+    let mut exporter = Client::new();
+    exporter.acquire(false, Priority::Normal);
+    exporter.acquire(false, Priority::Normal);
+
+    let mut importer = Client::new();
+    importer.acquire(false, Priority::Normal);
+    importer.import_state(exporter.export_state(0)).unwrap();
+
+    assert_eq!(importer.concurrency().in_flight(), 1);
+}
+
+#[test]
+fn imported_limits_are_clamped_to_the_policy() {
+    let with_limit = |limit: u32| {
+        let mut document: Value =
+            serde_json::from_str(&Client::new().export_state(0).to_json()).unwrap();
+        document["limiter"]["limit"] = limit.into();
+        ExportedState::from_json(&document.to_string()).unwrap()
+    };
+
+    // A hostile document must not leave the client rejecting forever.
+    let mut client = Client::new();
+    client.import_state(with_limit(0)).unwrap();
+    assert_eq!(client.concurrency().limit(), 1);
+    assert_eq!(client.acquire(false, Priority::Normal), Admission::Acquired);
+
+    // A limit learned under a larger `max_limit` is cut to this policy's.
+    let mut client = Client::new();
+    client.import_state(with_limit(u32::MAX)).unwrap();
+    assert_eq!(
+        client.concurrency().limit(),
+        ConcurrencyPolicy::default().max_limit
+    );
+}
+
+#[test]
+fn imported_samples_are_cut_to_the_window() {
+    // `warmed_up` recorded 10..=500ms under the default window of 100.
+    let document = warmed_up().export_state(0);
+
+    let mut client = Client::with_policy(Policy {
+        timeout: TimeoutPolicy {
+            window: 10,
+            min_samples: 5,
+            ..TimeoutPolicy::default()
+        },
+        ..Policy::default()
+    });
+    client.import_state(document).unwrap();
+
+    let search = client.latency_stats().partition(Some("search")).unwrap();
+    let samples: Vec<u32> = search.samples().collect();
+    assert_eq!(samples, (41..=50).map(|n| n * 10).collect::<Vec<_>>());
+    assert_eq!(search.attempts, 50);
+    // p99 of the kept samples, times 1.5.
+    assert_eq!(client.recommended_timeout_ms(Some("search")), 750);
+}
+
+#[test]
+fn document_is_versioned() {
+    let document: Value = serde_json::from_str(&Client::new().export_state(42).to_json()).unwrap();
+
+    assert_eq!(document["version"], STATE_VERSION);
+    assert_eq!(document["exported_at_ms"], 42);
+
+    let mut newer = document.clone();
+    newer["version"] = (STATE_VERSION + 1).into();
+    newer["auth"] = "reshaped".into();
+
+    assert_eq!(
+        ExportedState::from_json(&newer.to_string()),
+        Err(StateError::UnsupportedVersion(STATE_VERSION + 1))
+    );
+    assert!(matches!(
+        ExportedState::from_json("{\"version\": 1}"),
+        Err(StateError::Malformed(_))
+    ));
+}
+
+#[test]
+fn export_and_import_through_the_c_abi() {
+    let source = tc_client_new();
+    let partition = CString::new("search").unwrap();
+    for latency in 1..=50 {
+        transport_core::ffi::tc_record_attempt(
+            source,
+            partition.as_ptr(),
+            latency * 10,
+            std::ptr::null(),
+        );
+    }
+
+    let exported = tc_client_export_state(source, 1_000);
+    assert!(!exported.is_null());
+    let document = unsafe { CStr::from_ptr(exported) }.to_owned();
+    tc_string_free(exported);
+
+    let target = tc_client_new();
+    assert!(tc_client_import_state(target, document.as_ptr()));
+    assert_eq!(
+        tc_recommended_timeout_ms(target, partition.as_ptr()),
+        tc_recommended_timeout_ms(source, partition.as_ptr())
+    );
+
+    let garbage = CString::new("{}").unwrap();
+    assert!(!tc_client_import_state(target, garbage.as_ptr()));
+    assert_eq!(tc_last_status(), tc_status_t::TC_STATUS_INVALID_ARGUMENT);
+
+    tc_client_free(source);
+    tc_client_free(target);
+}
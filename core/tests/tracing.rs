@@ -0,0 +1,114 @@
+//! Run with `cargo test --features tracing`.
+#![cfg(feature = "tracing")]
+
+use std::fmt::Debug;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+use transport_core::{
+    client::Client,
+    model::{HttpMethod, Outcome, Priority, RequestContext},
+};
+
+/// Collects span names with their fields, and event messages.
+#[derive(Default)]
+struct Collector {
+    next_id: AtomicU64,
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields(format!("span {}", span.metadata().name()));
+        span.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields("event".to_string());
+        event.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn ctx() -> RequestContext {
+    RequestContext {
+        method: HttpMethod::GET,
+        attempt: 1,
+        max_attempts: 3,
+        idempotency_key: Some("order-42".to_string()),
+        allow_non_idempotent_retry: false,
+        partition: Some("api".to_string()),
+        priority: Priority::Normal,
+    }
+}
+
+#[test]
+fn decide_emits_a_span_and_the_decision() {
+    let collector = Collector::default();
+    let lines = collector.lines.clone();
+
+    tracing::subscriber::with_default(collector, || {
+        let mut client = Client::new();
+        client.decide(&ctx(), Outcome::TimeoutError, None, None);
+    });
+
+    let lines = lines.lock().unwrap();
+    assert!(lines[0].starts_with("span decide"), "{lines:?}");
+    for field in [
+        "key=\"order-42\"",
+        "partition=\"api\"",
+        "attempt=1",
+        "outcome=TimeoutError",
+    ] {
+        assert!(lines[0].contains(field), "{field} missing: {}", lines[0]);
+    }
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("message=decided") && line.contains("reason: Timeout")));
+}
+
+#[test]
+fn limit_changes_are_reported() {
+    let collector = Collector::default();
+    let lines = collector.lines.clone();
+
+    tracing::subscriber::with_default(collector, || {
+        let mut client = Client::new();
+        client.acquire(false, Priority::Normal);
+        client.release(100, Some(&Outcome::TimeoutError));
+    });
+
+    let lines = lines.lock().unwrap();
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("message=concurrency limit changed")));
+}
@@ -0,0 +1,27 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use transport_core::ffi::{
+    tc_abi_version, tc_feature_supported, tc_library_version, TRANSPORT_CORE_ABI_VERSION,
+};
+
+#[test]
+fn abi_version_matches_header_constant() {
+    assert_eq!(tc_abi_version(), TRANSPORT_CORE_ABI_VERSION);
+}
+
+#[test]
+fn library_version_is_crate_version() {
+    let version = unsafe { CStr::from_ptr(tc_library_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn feature_query() {
+    let hedging = CString::new("hedging").unwrap();
+    let unknown = CString::new("teleportation").unwrap();
+
+    assert!(tc_feature_supported(hedging.as_ptr()));
+    assert!(!tc_feature_supported(unknown.as_ptr()));
+    assert!(!tc_feature_supported(ptr::null()));
+}
@@ -0,0 +1,116 @@
+//! Runs under a local wasm runtime (Node.js), no browser needed:
+//!
+//! ```sh
+//! wasm-pack test --node --features wasm
+//! ```
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use transport_core::wasm::WasmClient;
+
+#[derive(Debug, Deserialize)]
+struct DecisionView {
+    action: String,
+    after_ms: Option<u32>,
+    retry_reason: Option<String>,
+    fail_reason: Option<String>,
+    retryable: bool,
+}
+
+// Plain objects, as a JS caller would pass them (not `Map`s).
+fn js(value: serde_json::Value) -> JsValue {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .unwrap()
+}
+
+fn ctx(method: &str, attempt: u8) -> JsValue {
+    js(json!({
+        "method": method,
+        "attempt": attempt,
+        "max_attempts": 3,
+        "idempotency_key": null,
+        "allow_non_idempotent_retry": false,
+    }))
+}
+
+fn decide(
+    client: &mut WasmClient,
+    ctx: JsValue,
+    outcome: serde_json::Value,
+    auth: Option<&str>,
+) -> DecisionView {
+    let value = client
+        .decide(ctx, js(outcome), auth.map(str::to_string), None)
+        .unwrap();
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn rate_limited_retry_keeps_hint() {
+    let mut client = WasmClient::new();
+
+    let view = decide(
+        &mut client,
+        ctx("GET", 1),
+        json!({ "RateLimited": { "retry_after_ms": 3000 } }),
+        None,
+    );
+
+    assert_eq!(view.action, "RETRY");
+    assert_eq!(view.after_ms, Some(3000));
+    assert_eq!(view.retry_reason.as_deref(), Some("RateLimited"));
+}
+
+#[wasm_bindgen_test]
+fn auth_refresh_then_fail_on_second_401() {
+    let mut client = WasmClient::new();
+
+    let first = decide(
+        &mut client,
+        ctx("GET", 1),
+        json!({ "HttpStatus": 401 }),
+        Some("RefreshAndRetry"),
+    );
+    assert_eq!(first.action, "REFRESH_AND_RETRY");
+
+    let second = decide(
+        &mut client,
+        ctx("GET", 2),
+        json!({ "HttpStatus": 401 }),
+        Some("RefreshAndRetry"),
+    );
+    assert_eq!(second.action, "FAIL");
+    assert_eq!(second.fail_reason.as_deref(), Some("AuthFailed"));
+    assert!(!second.retryable);
+}
+
+#[wasm_bindgen_test]
+fn policy_objects_configure_the_client() {
+    let mut client = WasmClient::with_policy(js(json!({
+        "retry": { "hint_mode": "MaxOfHintAndBackoff" }
+    })))
+    .unwrap();
+
+    let view = decide(
+        &mut client,
+        ctx("GET", 1),
+        json!({ "RateLimited": { "retry_after_ms": 10 } }),
+        None,
+    );
+
+    assert_eq!(view.after_ms, Some(1500));
+}
+
+#[wasm_bindgen_test]
+fn invalid_input_is_an_error_not_a_panic() {
+    let mut client = WasmClient::new();
+
+    assert!(client
+        .decide(ctx("FETCH", 1), js(json!("NetworkError")), None, None)
+        .is_err());
+}
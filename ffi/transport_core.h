@@ -1,139 +1,624 @@
+/* Generated by cbindgen from core/src/ffi.rs. Do not edit by hand. */
+
 #ifndef TRANSPORT_CORE_H
 #define TRANSPORT_CORE_H
 
-#ifdef __cplusplus
-extern "C" {
-#endif
-
 #include <stdint.h>
 #include <stdbool.h>
 
-/* ============================
- * ABI VERSION
- * ============================ */
-#define TRANSPORT_CORE_ABI_VERSION 1
+/**
+ * ABI version of this library. Bumped on any breaking change to the
+ * C interface.
+ */
+#define TRANSPORT_CORE_ABI_VERSION 2
 
-/* ============================
- * OPAQUE HANDLE
- * ============================ */
-typedef struct transport_core_client transport_core_client_t;
+/**
+ * Admission (from `tc_acquire`).
+ */
+typedef enum {
+  TC_ADMISSION_ACQUIRED = 0,
+  TC_ADMISSION_QUEUED = 1,
+  TC_ADMISSION_REJECTED = 2,
+} tc_admission_t;
 
-/* ============================
- * ENUMS
- * ============================ */
+/**
+ * Auth decision (from host).
+ */
+typedef enum {
+  TC_AUTH_REFRESH_AND_RETRY = 0,
+  TC_AUTH_FAIL = 1,
+} tc_auth_decision_t;
 
-/* HTTP Method */
+/**
+ * Coarse decision returned by `tc_decide`.
+ */
+typedef enum {
+  TC_DECISION_PROCEED = 0,
+  TC_DECISION_RETRY = 1,
+  TC_DECISION_REFRESH_AND_RETRY = 2,
+  TC_DECISION_FAIL = 3,
+} tc_decision_t;
+
+/**
+ * Reason for a `TC_DECISION_FAIL`, from `tc_last_fail_reason`.
+ * Values match `model::FailReason`.
+ */
 typedef enum {
-    TC_HTTP_GET = 0,
-    TC_HTTP_POST,
-    TC_HTTP_PUT,
-    TC_HTTP_DELETE,
-    TC_HTTP_HEAD,
-    TC_HTTP_OPTIONS
+  /**
+   * The last decision was not a failure.
+   */
+  TC_FAIL_REASON_NONE = 0,
+  TC_FAIL_REASON_MAX_ATTEMPTS = 1,
+  TC_FAIL_REASON_AUTH_FAILED = 2,
+  TC_FAIL_REASON_HARD_BLOCKED = 3,
+  TC_FAIL_REASON_SHED = 4,
+  TC_FAIL_REASON_UNKNOWN = 255,
+} tc_fail_reason_t;
+
+/**
+ * How a RATE_LIMITED `retry_after_ms` hint is combined with the
+ * computed backoff.
+ */
+typedef enum {
+  TC_HINT_ONLY = 0,
+  TC_HINT_MAX_OF_BACKOFF = 1,
+  TC_HINT_PLUS_JITTER = 2,
+} tc_hint_mode_t;
+
+typedef enum {
+  TC_HTTP_GET = 0,
+  TC_HTTP_POST,
+  TC_HTTP_PUT,
+  TC_HTTP_DELETE,
+  TC_HTTP_HEAD,
+  TC_HTTP_OPTIONS,
 } tc_http_method_t;
 
-/* Semantic Outcome */
+/**
+ * Concurrency limit algorithm.
+ */
 typedef enum {
-    TC_OUTCOME_NETWORK_ERROR = 0,
-    TC_OUTCOME_TIMEOUT_ERROR,
-    TC_OUTCOME_HTTP_STATUS,
-
-    /* semantic-first outcomes */
-    TC_OUTCOME_RATE_LIMITED,
-    TC_OUTCOME_BLOCKED,
-    TC_OUTCOME_CAPTCHA
-} tc_outcome_kind_t;
+  TC_LIMIT_AIMD = 0,
+  TC_LIMIT_VEGAS = 1,
+} tc_limit_algorithm_t;
 
-/* Decision */
+/**
+ * Semantic outcome.
+ */
 typedef enum {
-    TC_DECISION_PROCEED = 0,
-    TC_DECISION_RETRY,
-    TC_DECISION_REFRESH_AND_RETRY,
-    TC_DECISION_FAIL
-} tc_decision_t;
+  TC_OUTCOME_NETWORK_ERROR = 0,
+  TC_OUTCOME_TIMEOUT_ERROR,
+  TC_OUTCOME_HTTP_STATUS,
+  TC_OUTCOME_RATE_LIMITED,
+  TC_OUTCOME_BLOCKED,
+  TC_OUTCOME_CAPTCHA,
+} tc_outcome_kind_t;
 
-/* Auth Decision (from host) */
+/**
+ * Request priority. Lower priorities are shed first.
+ *
+ * NORMAL is 0 so that a zeroed context has normal priority; the values
+ * are not in shedding order.
+ */
 typedef enum {
-    TC_AUTH_REFRESH_AND_RETRY = 0,
-    TC_AUTH_FAIL
-} tc_auth_decision_t;
+  TC_PRIORITY_NORMAL = 0,
+  TC_PRIORITY_CRITICAL = 1,
+  TC_PRIORITY_BACKGROUND = 2,
+} tc_priority_t;
 
-/* Retry Reason */
+/**
+ * Reason for a `TC_DECISION_RETRY`, from `tc_last_retry_reason`.
+ * Values match `model::RetryReason`.
+ */
 typedef enum {
-    TC_RETRY_REASON_NETWORK = 0,
-    TC_RETRY_REASON_TIMEOUT,
-    TC_RETRY_REASON_RATE_LIMITED,
-    TC_RETRY_REASON_AUTH_EXPIRED
+  /**
+   * The last decision was not a retry.
+   */
+  TC_RETRY_REASON_NONE = 0,
+  TC_RETRY_REASON_NETWORK = 1,
+  TC_RETRY_REASON_TIMEOUT = 2,
+  TC_RETRY_REASON_RATE_LIMITED = 3,
+  TC_RETRY_REASON_AUTH_EXPIRED = 4,
 } tc_retry_reason_t;
 
-/* Fail Reason */
+/**
+ * Status of the most recent call on the calling thread, from
+ * `tc_last_status`.
+ */
 typedef enum {
-    TC_FAIL_REASON_UNKNOWN = 0,
-    TC_FAIL_REASON_MAX_ATTEMPTS,
-    TC_FAIL_REASON_AUTH_FAILED,
-    TC_FAIL_REASON_HARD_BLOCKED
-} tc_fail_reason_t;
+  TC_STATUS_OK = 0,
+  /**
+   * A required pointer was NULL or an integer was out of range.
+   */
+  TC_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * An enum argument or field held a value the enum does not define.
+   */
+  TC_STATUS_INVALID_ENUM = 2,
+  /**
+   * A string argument was not valid UTF-8.
+   */
+  TC_STATUS_INVALID_UTF8 = 3,
+  /**
+   * The library panicked. The client's state may be inconsistent and
+   * it should be freed.
+   */
+  TC_STATUS_PANIC = 4,
+} tc_status_t;
 
-/* ============================
- * STRUCTS
- * ============================ */
+/**
+ * Opaque client handle.
+ *
+ * Holds authentication coordination state and the client's policy.
+ * Allocated by `tc_client_new`, released by `tc_client_free`.
+ */
+typedef struct transport_core_client_t transport_core_client_t;
 
-/* Request Context */
+/**
+ * Adaptive timeout policy.
+ */
 typedef struct {
-    tc_http_method_t method;
-    uint8_t attempt;
-    uint8_t max_attempts;
-    bool allow_non_idempotent_retry;
-    const char* idempotency_key; /* nullable */
-} tc_request_context_t;
+  /**
+   * Latency percentile, 1..=100.
+   */
+  uint8_t percentile;
+  /**
+   * Multiplier applied to the percentile, in percent.
+   */
+  uint32_t factor_percent;
+  uint32_t min_ms;
+  uint32_t max_ms;
+  /**
+   * Used until `min_samples` attempts were reported.
+   */
+  uint32_t default_ms;
+  uint32_t min_samples;
+  /**
+   * Most recent samples kept per partition.
+   */
+  uint32_t window;
+} tc_timeout_policy_t;
 
-/* Outcome */
+/**
+ * Adaptive concurrency policy.
+ */
 typedef struct {
-    tc_outcome_kind_t kind;
+  /**
+   * A `tc_limit_algorithm_t` value.
+   */
+  int algorithm;
+  uint32_t initial_limit;
+  uint32_t min_limit;
+  uint32_t max_limit;
+  /**
+   * Share of the limit removed on overload, in percent.
+   */
+  uint8_t decrease_percent;
+  /**
+   * 0 = reject as soon as the limit is reached.
+   */
+  uint32_t max_queue;
+  /**
+   * Share of the limit, in percent, `TC_PRIORITY_BACKGROUND` may use.
+   */
+  uint8_t background_share_percent;
+  uint32_t vegas_alpha;
+  uint32_t vegas_beta;
+} tc_concurrency_policy_t;
 
-    /* Only valid for HTTP_STATUS */
-    uint16_t http_status;
+/**
+ * Request context.
+ */
+typedef struct {
+  /**
+   * A `tc_http_method_t` value.
+   */
+  int method;
+  uint8_t attempt;
+  uint8_t max_attempts;
+  bool allow_non_idempotent_retry;
+  /**
+   * Nullable.
+   */
+  const char *idempotency_key;
+  /**
+   * Nullable. Added in ABI v2.
+   */
+  const char *partition;
+  /**
+   * A `tc_priority_t` value. Added in ABI v2.
+   */
+  int priority;
+} tc_request_context_t;
 
-    /* Only valid for RATE_LIMITED */
-    uint32_t retry_after_ms;
+/**
+ * Outcome.
+ */
+typedef struct {
+  /**
+   * A `tc_outcome_kind_t` value.
+   */
+  int kind;
+  /**
+   * Only valid for `TC_OUTCOME_HTTP_STATUS`.
+   */
+  uint16_t http_status;
+  /**
+   * Only valid for `TC_OUTCOME_RATE_LIMITED`.
+   */
+  uint32_t retry_after_ms;
 } tc_outcome_t;
 
-/* ============================
- * LIFECYCLE
- * ============================ */
+/**
+ * Snapshot of a client's state (from `tc_client_state`).
+ */
+typedef struct {
+  bool refresh_in_progress;
+  bool refresh_attempted;
+  uint32_t concurrency_limit;
+  uint32_t in_flight;
+  uint32_t queued;
+  /**
+   * Named latency partitions seen so far.
+   */
+  uint32_t partition_count;
+  uint64_t hedge_requests;
+  uint64_t hedges_sent;
+  /**
+   * Hedges planned for requests not reported yet.
+   */
+  uint64_t hedges_pending;
+} tc_client_state_t;
+
+/**
+ * One decision, passed to a `tc_observer_fn`. Pointers are only valid
+ * during the callback.
+ */
+typedef struct {
+  /**
+   * A `tc_http_method_t` value.
+   */
+  int method;
+  uint8_t attempt;
+  uint8_t max_attempts;
+  /**
+   * A `tc_priority_t` value.
+   */
+  int priority;
+  /**
+   * Nullable.
+   */
+  const char *partition;
+  /**
+   * A `tc_outcome_kind_t` value.
+   */
+  int outcome_kind;
+  uint16_t http_status;
+  uint32_t retry_after_ms;
+  tc_decision_t decision;
+  /**
+   * As from `tc_last_retry_after_ms`.
+   */
+  uint32_t after_ms;
+  /**
+   * A `tc_retry_reason_t` value.
+   */
+  uint8_t retry_reason;
+  /**
+   * A `tc_fail_reason_t` value.
+   */
+  uint8_t fail_reason;
+  bool fail_retryable;
+  /**
+   * As from `tc_last_timeout_ms`.
+   */
+  uint32_t timeout_ms;
+} tc_decision_event_t;
+
+/**
+ * Called after every decision with the `userdata` it was registered
+ * with.
+ */
+typedef void (*tc_observer_fn)(void *userdata, const tc_decision_event_t *event);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+transport_core_client_t *tc_client_new(void);
+
+/**
+ * NULL is ignored.
+ */
+void tc_client_free(transport_core_client_t *ptr);
+
+/**
+ * Status of the most recent call on the calling thread. Every function
+ * except the version queries and the two error getters records one.
+ */
+tc_status_t tc_last_status(void);
+
+/**
+ * Message describing the most recent failed call on the calling thread,
+ * or NULL if it succeeded. Owned by the library and valid until the
+ * next call on the same thread; MUST NOT be freed.
+ */
+const char *tc_last_error_message(void);
+
+/**
+ * ABI version the library was built with. Hosts MUST refuse to use the
+ * library when it differs from the `TRANSPORT_CORE_ABI_VERSION` they
+ * were built against.
+ */
+uint32_t tc_abi_version(void);
+
+/**
+ * Semver version of the library, e.g. "0.1.0". The string is static and
+ * MUST NOT be freed.
+ */
+const char *tc_library_version(void);
+
+/**
+ * Whether the library supports the named capability, e.g. "hedging".
+ * Unknown names return false, as do NULL and non-UTF-8 input (which
+ * also set `tc_last_status`).
+ */
+bool tc_feature_supported(const char *name);
 
-transport_core_client_t* tc_client_new(void);
-void tc_client_free(transport_core_client_t* client);
+/**
+ * `mode`: a `tc_hint_mode_t` value.
+ *
+ * `max_jitter_ms` is only used by `TC_HINT_PLUS_JITTER`. A non-zero
+ * `jitter_seed` fixes the jitter, for reproducible tests; 0 keeps the
+ * seed each client draws for itself.
+ */
+void tc_client_set_hint_mode(transport_core_client_t *client,
+                             int mode,
+                             uint32_t max_jitter_ms,
+                             uint64_t jitter_seed);
 
-/* ============================
- * DECISION ENGINE
- * ============================ */
+/**
+ * Hedging is disabled until `max_hedges > 0`. `delay_ms` is how long
+ * the host waits without a response before sending each hedge.
+ */
+void tc_client_set_hedge_policy(transport_core_client_t *client,
+                                uint8_t max_hedges,
+                                uint32_t delay_ms);
 
-/*
- * refresh_result:
- *   -1 = refresh not attempted
- *    0 = refresh failed
- *    1 = refresh succeeded
+/**
+ * Adapts hedging to observed traffic; 0 disables either part.
+ *
+ * With `delay_percentile` (1..=100), hedges are planned after that
+ * latency percentile of the request's partition once it has
+ * `min_samples` samples, and after `delay_ms` until then. With
+ * `budget_percent`, hedges planned stay within that percentage of the
+ * requests reported through `tc_report_hedge_result`.
+ *
+ * Fails with TC_STATUS_INVALID_ARGUMENT if `delay_percentile` is over
+ * 100.
  */
-tc_decision_t tc_decide(
-    transport_core_client_t* client,
-    const tc_request_context_t* ctx,
-    const tc_outcome_t* outcome,
-    tc_auth_decision_t auth_decision,
-    int8_t refresh_result
-);
+void tc_client_set_hedge_adaptation(transport_core_client_t *client,
+                                    uint8_t delay_percentile,
+                                    uint32_t min_samples,
+                                    uint32_t budget_percent);
 
-/* ============================
- * DECISION DETAILS (GETTERS)
- * ============================ */
+void tc_client_set_timeout_policy(transport_core_client_t *client,
+                                  const tc_timeout_policy_t *policy);
+
+/**
+ * Replaces the concurrency policy and restarts limit adaptation.
+ */
+void tc_client_set_concurrency_policy(transport_core_client_t *client,
+                                      const tc_concurrency_policy_t *policy);
+
+/**
+ * Evaluates retry eligibility and auth coordination, updating the
+ * client's auth state. Details are available through the
+ * `tc_last_*` getters.
+ *
+ * `auth_decision`: a `tc_auth_decision_t` value.
+ * `refresh_result`: -1 = refresh not attempted, 0 = refresh failed,
+ * 1 = refresh succeeded.
+ *
+ * On invalid arguments returns `TC_DECISION_FAIL` without touching the
+ * client; check `tc_last_status` to tell it from a policy failure.
+ */
+tc_decision_t tc_decide(transport_core_client_t *client,
+                        const tc_request_context_t *ctx,
+                        const tc_outcome_t *outcome,
+                        int auth_decision,
+                        int8_t refresh_result);
+
+uint32_t tc_last_retry_after_ms(const transport_core_client_t *client);
+
+/**
+ * Recommended per-attempt timeout for a RETRY / REFRESH_AND_RETRY,
+ * else 0.
+ */
+uint32_t tc_last_timeout_ms(const transport_core_client_t *client);
+
+/**
+ * A `tc_retry_reason_t` value.
+ */
+uint8_t tc_last_retry_reason(const transport_core_client_t *client);
+
+/**
+ * A `tc_fail_reason_t` value.
+ */
+uint8_t tc_last_fail_reason(const transport_core_client_t *client);
+
+bool tc_last_fail_retryable(const transport_core_client_t *client);
+
+/**
+ * JSON-in/JSON-out variant of `tc_decide` using the client's state.
+ *
+ * input:  `{"ctx": {...}, "outcome": ..., "auth_decision": ...,
+ *           "refresh_result": ...}`
+ * output: `{"decision": ..., "auth_state": {...}}` or `{"error": "..."}`
+ *
+ * Returns 0 when `*output` holds a decision. Returns -1 on NULL
+ * arguments or non-UTF-8 input (nothing written; see `tc_last_status`),
+ * or 1 on a malformed request (`*output` holds the error).
+ * `*output` MUST be freed with `tc_string_free`.
+ */
+int32_t tc_decide_json(transport_core_client_t *client, const char *input, char **output);
+
+/**
+ * Frees a string returned by this library. NULL is ignored.
+ */
+void tc_string_free(char *s);
+
+/**
+ * Reports one finished attempt.
+ *
+ * `partition`: nullable; NULL uses the client-wide default partition.
+ * `outcome`: nullable; NULL means the attempt succeeded.
+ */
+void tc_record_attempt(transport_core_client_t *client,
+                       const char *partition,
+                       uint32_t latency_ms,
+                       const tc_outcome_t *outcome);
+
+uint32_t tc_recommended_timeout_ms(const transport_core_client_t *client, const char *partition);
+
+/**
+ * Call before sending a request.
+ *
+ * - ACQUIRED: send it, then call `tc_release` when it finishes.
+ * - QUEUED: wait, then call again with `queued = true`
+ *   (or `tc_cancel_queued` to give up).
+ * - REJECTED: do not send it.
+ *
+ * Queued requests are served before new ones. BACKGROUND requests are
+ * rejected beyond their share of the limit; CRITICAL requests may queue
+ * up to one limit's worth past `max_queue`.
+ *
+ * `priority`: a `tc_priority_t` value.
+ */
+tc_admission_t tc_acquire(transport_core_client_t *client, bool queued, int priority);
 
-uint32_t tc_last_retry_after_ms(const transport_core_client_t* client);
-uint8_t  tc_last_retry_reason(const transport_core_client_t* client);
-uint8_t  tc_last_fail_reason(const transport_core_client_t* client);
-bool     tc_last_fail_retryable(const transport_core_client_t* client);
+void tc_cancel_queued(transport_core_client_t *client);
+
+/**
+ * `outcome`: nullable; NULL means the request succeeded.
+ */
+void tc_release(transport_core_client_t *client, uint32_t latency_ms, const tc_outcome_t *outcome);
+
+uint32_t tc_concurrency_limit(const transport_core_client_t *client);
+
+/**
+ * `hedges_sent`: hedges already sent for this request (0 before the
+ * first).
+ *
+ * Returns true and writes `*after_ms` if the host should send another
+ * duplicate attempt once `after_ms` elapse without a response. Only
+ * idempotent methods are hedged. The planned hedge counts against the
+ * budget until the request is reported through `tc_report_hedge_result`.
+ */
+bool tc_plan_hedge(transport_core_client_t *client,
+                   const tc_request_context_t *ctx,
+                   uint8_t hedges_sent,
+                   uint32_t *after_ms);
+
+/**
+ * Reports a finished hedged request and the number of hedges it sent,
+ * settling the hedges planned for it.
+ */
+void tc_report_hedge_result(transport_core_client_t *client, uint8_t hedges_sent);
+
+/**
+ * Clears the auth coordination state so a later 401 may refresh again.
+ */
+void tc_client_reset_auth(transport_core_client_t *client);
+
+/**
+ * Forgets all learned state but keeps the policy. In-flight and queued
+ * counts are kept so outstanding slots can still be released.
+ */
+void tc_client_reset(transport_core_client_t *client);
+
+void tc_client_state(const transport_core_client_t *client, tc_client_state_t *state);
+
+/**
+ * Exports the client's learned state as a versioned JSON document (see
+ * `client::ExportedState`), for hosts that persist it between
+ * processes. `now_ms` is the host's clock.
+ *
+ * Returns NULL on error. The result MUST be freed with `tc_string_free`.
+ */
+char *tc_client_export_state(const transport_core_client_t *client, uint64_t now_ms);
+
+/**
+ * Replaces the client's learned state with a document from
+ * `tc_client_export_state`. The policy and the in-flight / queued
+ * counts are kept; the imported concurrency limit and latency samples
+ * are fitted to the policy.
+ *
+ * Returns false, leaving the client untouched, if the document is
+ * malformed or from an unsupported version.
+ */
+bool tc_client_import_state(transport_core_client_t *client, const char *state);
+
+/**
+ * Turns decision explanations on or off (off by default).
+ */
+void tc_client_set_explain(transport_core_client_t *client, bool explain);
+
+/**
+ * Rules evaluated by the last `tc_decide` as JSON:
+ * `{"steps": [{"rule": "attempt_limit", ...}, ...], "decision": ...}`.
+ *
+ * Returns NULL when explanations are off or nothing was decided yet.
+ * The result MUST be freed with `tc_string_free`.
+ */
+char *tc_last_explanation_json(const transport_core_client_t *client);
+
+/**
+ * Registers `observer`, called after every decision the client makes
+ * (including through `tc_decide_json`) with `userdata`. NULL removes
+ * the observer.
+ *
+ * The callback runs on the thread calling `tc_decide`, before it
+ * returns, and MUST NOT call back into this client.
+ */
+void tc_client_set_observer(transport_core_client_t *client,
+                            tc_observer_fn observer,
+                            void *userdata);
+
+/**
+ * Decision counters and histograms as JSON, shaped after the Prometheus
+ * data model:
+ *
+ * ```json
+ * {"counters": [{"name": "tc_decisions_total",
+ *                "labels": {"partition": "", "decision": "retry", "reason": "timeout"},
+ *                "value": 3}, ...],
+ *  "histograms": [{"name": "tc_retry_delay_ms", "labels": {"partition": ""},
+ *                  "buckets": [{"le": 0, "count": 0}, ..., {"le": null, "count": 3}],
+ *                  "sum": 1200, "count": 3}]}
+ * ```
+ *
+ * Bucket counts are cumulative and `"le": null` is `+Inf`. The result
+ * MUST be freed with `tc_string_free`.
+ */
+char *tc_client_metrics_json(const transport_core_client_t *client);
+
+/**
+ * Turns recording of `tc_decide` calls on or off (off by default).
+ * Turning it on starts the log with the client's policy and auth state.
+ */
+void tc_client_set_recording(transport_core_client_t *client, bool recording);
+
+/**
+ * Drains the decisions recorded since the last call as JSON lines, one
+ * entry per line; concatenated, the chunks form one log for
+ * `transport-core replay`. Returns an empty string when nothing was
+ * recorded. The result MUST be freed with `tc_string_free`.
+ */
+char *tc_client_take_recording(transport_core_client_t *client);
 
 #ifdef __cplusplus
-}
-#endif
+} // extern "C"
+#endif // __cplusplus
 
 #endif /* TRANSPORT_CORE_H */